@@ -1,8 +1,8 @@
 use std::collections::{HashMap, HashSet};
 
-use sats::assignment::{Assignment, Truth};
+use sats::assignment::Truth;
 use sats::cnf::CNF;
-use sats::solver::Solver;
+use sats::solver::{Registry, SolveResult, SolverSpec};
 use sats::{dimacs, eval};
 
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
@@ -17,24 +17,31 @@ enum Expected {
 struct Problem {
     cnf: CNF,
     expected: Expected,
-    runs_on: HashSet<Solver>,
+    /// Solvers that finish on the instance in reasonable time. DPLL needs
+    /// from 15 s to minutes on the hard instances, so it is not run on them.
+    runs_on: HashSet<&'static str>,
 }
 
 trait AssertSATResult {
     fn assert_sat_result(&self, cnf: &CNF, expected: Expected);
 }
 
-impl AssertSATResult for Option<Assignment> {
+impl AssertSATResult for SolveResult {
     fn assert_sat_result(&self, cnf: &CNF, expected: Expected) {
         match (self, expected) {
-            (Some(model), Expected::SAT) => {
+            (SolveResult::Satisfiable(model), Expected::SAT) => {
                 if eval::eval(cnf, &model) != Truth::True {
                     panic!("SAT with invalid assignment");
                 }
             }
-            (None, Expected::SAT) => panic!("SAT is expected, but UNSAT is returned"),
-            (Some(_), Expected::UNSAT) => panic!("UNSAT is expected, but SAT is returned"),
-            (None, Expected::UNSAT) => {}
+            (SolveResult::Unsatisfiable, Expected::SAT) => {
+                panic!("SAT is expected, but UNSAT is returned")
+            }
+            (SolveResult::Satisfiable(_), Expected::UNSAT) => {
+                panic!("UNSAT is expected, but SAT is returned")
+            }
+            (SolveResult::Unsatisfiable, Expected::UNSAT) => {}
+            (SolveResult::Unknown(interrupt), _) => panic!("UNKNOWN is returned: {}", interrupt),
         }
     }
 }
@@ -45,67 +52,67 @@ fn bench(c: &mut Criterion) {
             "p20",
             Expected::SAT,
             include_bytes!("data/p20.cnf").as_ref(),
-            [Solver::CDCL, Solver::DPLL].as_ref(),
+            ["cdcl", "dpll"].as_ref(),
         ),
         (
             "p50",
             Expected::UNSAT,
             include_bytes!("data/p50.cnf").as_ref(),
-            [Solver::CDCL, Solver::DPLL].as_ref(),
+            ["cdcl", "dpll"].as_ref(),
         ),
         (
             "zebra",
             Expected::SAT,
             include_bytes!("data/zebra_v155_c1135.cnf").as_ref(),
-            [Solver::CDCL, Solver::DPLL].as_ref(),
+            ["cdcl", "dpll"].as_ref(),
         ),
         (
             "hole6",
             Expected::UNSAT,
             include_bytes!("data/hole6.cnf").as_ref(),
-            [Solver::CDCL, Solver::DPLL].as_ref(),
+            ["cdcl", "dpll"].as_ref(),
         ),
         (
             "aim-50",
             Expected::SAT,
             include_bytes!("data/aim-50-1_6-yes1-4.cnf").as_ref(),
-            [Solver::CDCL, Solver::DPLL].as_ref(),
+            ["cdcl", "dpll"].as_ref(),
         ),
         (
             "aim-100",
             Expected::UNSAT,
             include_bytes!("data/aim-100-1_6-no-1.cnf").as_ref(),
-            [].as_ref(),
+            ["cdcl"].as_ref(),
         ),
         (
             "dubois20",
             Expected::UNSAT,
             include_bytes!("data/dubois20.cnf").as_ref(),
-            [Solver::CDCL].as_ref(),
+            ["cdcl"].as_ref(),
         ),
         (
             "dubois21",
             Expected::UNSAT,
             include_bytes!("data/dubois21.cnf").as_ref(),
-            [Solver::CDCL].as_ref(),
+            ["cdcl"].as_ref(),
         ),
         (
             "dubois22",
             Expected::UNSAT,
             include_bytes!("data/dubois22.cnf").as_ref(),
-            [Solver::CDCL].as_ref(),
+            ["cdcl"].as_ref(),
         ),
         (
             "par8",
             Expected::SAT,
             include_bytes!("data/par8-1-c.cnf").as_ref(),
-            [Solver::CDCL, Solver::DPLL].as_ref(),
+            ["cdcl", "dpll"].as_ref(),
         ),
         (
             "bf",
-            Expected::SAT,
+            Expected::UNSAT,
             include_bytes!("data/bf0432-007.cnf").as_ref(),
-            [].as_ref(),
+            ["cdcl"].as_ref(),
         ),
     ]
     .iter()
@@ -121,6 +128,7 @@ fn bench(c: &mut Criterion) {
     })
     .collect();
 
+    let registry = Registry::default();
     for (
         name,
         Problem {
@@ -133,17 +141,19 @@ fn bench(c: &mut Criterion) {
         let mut group = c.benchmark_group(&name);
         group.sample_size(10);
 
-        for solver in Solver::all() {
-            if runs_on.contains(&solver) {
-                group.bench_function(solver.to_string(), |b| {
+        for solver_name in registry.names() {
+            if runs_on.contains(solver_name) {
+                let spec = SolverSpec::new(solver_name);
+                group.bench_function(solver_name, |b| {
                     b.iter(|| {
+                        let mut solver = registry.create(&spec).unwrap();
                         solver
                             .run(black_box(cnf.clone()))
                             .assert_sat_result(&cnf, expected)
                     })
                 });
             } else {
-                info!("skipping benchmark {}/{}", name, solver);
+                info!("skipping benchmark {}/{}", name, solver_name);
             }
         }
 
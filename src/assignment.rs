@@ -1,15 +1,15 @@
-use std::collections::hash_map;
-use std::collections::HashMap;
-use std::{fmt, ops};
+use std::convert::TryFrom;
+use std::{error, fmt, iter, ops, vec};
 
-use crate::cnf::{Literal, Variable};
+use crate::cnf::{DisplayNamed, Literal, NameTable, Variable};
 
 use itertools::Itertools;
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum Truth {
     True,
     False,
+    Undefined,
 }
 
 impl fmt::Display for Truth {
@@ -17,6 +17,7 @@ impl fmt::Display for Truth {
         match self {
             Truth::True => f.pad("True"),
             Truth::False => f.pad("False"),
+            Truth::Undefined => f.pad("Undefined"),
         }
     }
 }
@@ -27,10 +28,31 @@ impl ops::Not for Truth {
         match self {
             Truth::True => Truth::False,
             Truth::False => Truth::True,
+            Truth::Undefined => Truth::Undefined,
         }
     }
 }
 
+/// Kleene conjunction: false if either side is false, undefined unless both are true.
+impl ops::BitAnd for Truth {
+    type Output = Truth;
+    fn bitand(self, rhs: Truth) -> Truth {
+        match (self, rhs) {
+            (Truth::False, _) | (_, Truth::False) => Truth::False,
+            (Truth::True, Truth::True) => Truth::True,
+            _ => Truth::Undefined,
+        }
+    }
+}
+
+/// Kleene disjunction: true if either side is true, undefined unless both are false.
+impl ops::BitOr for Truth {
+    type Output = Truth;
+    fn bitor(self, rhs: Truth) -> Truth {
+        !(!self & !rhs)
+    }
+}
+
 impl From<bool> for Truth {
     #[allow(clippy::match_bool)]
     fn from(x: bool) -> Truth {
@@ -41,32 +63,57 @@ impl From<bool> for Truth {
     }
 }
 
-impl Into<bool> for Truth {
-    fn into(self) -> bool {
-        self.as_bool()
+/// Fails on `Undefined`, which has no boolean value.
+impl TryFrom<Truth> for bool {
+    type Error = UndefinedTruth;
+    fn try_from(truth: Truth) -> Result<bool, UndefinedTruth> {
+        truth.as_bool().ok_or(UndefinedTruth)
     }
 }
 
+#[derive(Debug, PartialEq, Eq)]
+pub struct UndefinedTruth;
+
+impl fmt::Display for UndefinedTruth {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "undefined truth value has no boolean value")
+    }
+}
+
+impl error::Error for UndefinedTruth {}
+
 impl Truth {
-    pub fn as_bool(self) -> bool {
+    /// `None` if the value is `Undefined`.
+    pub fn as_bool(self) -> Option<bool> {
         match self {
-            Truth::True => true,
-            Truth::False => false,
+            Truth::True => Some(true),
+            Truth::False => Some(false),
+            Truth::Undefined => None,
         }
     }
+
+    pub fn is_defined(self) -> bool {
+        self != Truth::Undefined
+    }
 }
 
+/// Truth values indexed by variable; unassigned variables are `Undefined`.
 #[derive(Clone)]
-pub struct Assignment(HashMap<Variable, Truth>);
+pub struct Assignment(Vec<Truth>);
 
 impl fmt::Display for Assignment {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_named(&NameTable::new(), f)
+    }
+}
+
+impl DisplayNamed for Assignment {
+    fn fmt_named(&self, names: &NameTable, f: &mut fmt::Formatter) -> fmt::Result {
         f.write_str("{ ")?;
         f.write_str(
             &self
-                .0
                 .iter()
-                .map(|(var, truth)| format!("{} := {}", var, truth))
+                .map(|(var, truth)| format!("{} := {}", names.display(&var), truth))
                 .join(", "),
         )?;
         f.write_str(" }")?;
@@ -83,9 +130,21 @@ impl Default for Assignment {
 
 impl IntoIterator for Assignment {
     type Item = (Variable, Truth);
-    type IntoIter = hash_map::IntoIter<Variable, Truth>;
+    #[allow(clippy::type_complexity)]
+    type IntoIter = iter::FilterMap<
+        iter::Enumerate<vec::IntoIter<Truth>>,
+        fn((usize, Truth)) -> Option<(Variable, Truth)>,
+    >;
     fn into_iter(self) -> Self::IntoIter {
-        self.0.into_iter()
+        self.0.into_iter().enumerate().filter_map(defined)
+    }
+}
+
+fn defined((index, truth): (usize, Truth)) -> Option<(Variable, Truth)> {
+    if truth.is_defined() {
+        Some((Variable::from_index(index), truth))
+    } else {
+        None
     }
 }
 
@@ -94,25 +153,34 @@ impl Extend<(Variable, Truth)> for Assignment {
     where
         T: IntoIterator<Item = (Variable, Truth)>,
     {
-        self.0.extend(iter);
+        for (var, truth) in iter {
+            self.assign(&var, truth);
+        }
     }
 }
 
 impl Assignment {
     pub fn new() -> Assignment {
-        Assignment(HashMap::new())
+        Assignment(Vec::new())
     }
 
     pub fn clear(&mut self) {
         self.0.clear();
     }
 
+    /// Assigning `Undefined` removes the variable from the assignment.
     pub fn assign(&mut self, var: &Variable, truth: Truth) {
-        self.0.insert(var.clone(), truth);
+        if var.index() >= self.0.len() {
+            if !truth.is_defined() {
+                return;
+            }
+            self.0.resize(var.index() + 1, Truth::Undefined);
+        }
+        self.0[var.index()] = truth;
     }
 
     pub fn assign_true(&mut self, literal: &Literal) {
-        self.assign(literal.variable(), Truth::from(!literal.is_negated()));
+        self.assign(&literal.variable(), Truth::from(!literal.is_negated()));
     }
 
     pub fn assigned_true(mut self, literal: &Literal) -> Assignment {
@@ -121,6 +189,22 @@ impl Assignment {
     }
 
     pub fn get(&self, var: &Variable) -> Option<Truth> {
-        self.0.get(var).copied()
+        self.0
+            .get(var.index())
+            .copied()
+            .filter(|truth| truth.is_defined())
+    }
+
+    /// Assigned variables with their values, in order of index.
+    pub fn iter(&self) -> impl Iterator<Item = (Variable, Truth)> + '_ {
+        self.0
+            .iter()
+            .enumerate()
+            .filter_map(|(index, &truth)| defined((index, truth)))
+    }
+
+    /// Like `get`, but unassigned variables are `Undefined`.
+    pub fn value(&self, var: &Variable) -> Truth {
+        self.0.get(var.index()).copied().unwrap_or(Truth::Undefined)
     }
 }
@@ -1,20 +1,32 @@
 use std::collections::{HashMap, HashSet};
-use std::{char, fmt, ops, str};
+use std::{fmt, ops, str};
 
 use itertools::Itertools;
 
+mod arena;
 mod clause_id;
 mod clauses;
-pub use clause_id::ClauseID;
+mod name_table;
+pub use arena::{ClauseArena, ClauseRef, Relocation, Word};
+pub use clause_id::{ClauseID, ClauseIDGenerator};
+pub use clauses::Checkpoint;
+pub use name_table::{DisplayNamed, NameConflict, NameTable, Named};
 
 #[derive(Debug, Clone)]
 pub struct CNF {
     clauses: clauses::Clauses,
+    names: NameTable,
 }
 
 impl fmt::Display for CNF {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.write_str(&self.clauses.into_iter().join(" ∧ "))
+        f.write_str(
+            &self
+                .clauses
+                .iter()
+                .map(|c| self.names.display(c).to_string())
+                .join(" ∧ "),
+        )
     }
 }
 
@@ -22,26 +34,46 @@ impl str::FromStr for CNF {
     type Err = ParseVariableError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut names = NameTable::new();
         let clauses = s
             .split(" ∧ ")
             .flat_map(|sub| sub.split(" /\\ "))
-            .map(|c| c.parse())
+            .map(|c| names.parse_clause(c))
             .collect::<Result<Vec<_>, _>>()?;
-        Ok(CNF::from_clauses(clauses))
+        Ok(CNF::with_names(clauses, names))
     }
 }
 
 impl CNF {
+    /// A CNF whose variables have no names.
     pub fn from_clauses<T>(clauses: T) -> CNF
+    where
+        T: IntoIterator<Item = Clause>,
+    {
+        CNF::with_names(clauses, NameTable::new())
+    }
+
+    /// A CNF whose variables are named in `names`.
+    pub fn with_names<T>(clauses: T, names: NameTable) -> CNF
     where
         T: IntoIterator<Item = Clause>,
     {
         CNF {
             clauses: clauses.into_iter().collect(),
+            names,
         }
     }
 
-    pub fn most_occurred_literal(&self) -> Option<&Literal> {
+    pub fn names(&self) -> &NameTable {
+        &self.names
+    }
+
+    /// The names, to intern the variables of clauses added later.
+    pub fn names_mut(&mut self) -> &mut NameTable {
+        &mut self.names
+    }
+
+    pub fn most_occurred_literal(&self) -> Option<Literal> {
         self.clauses.most_occurred_literal()
     }
 
@@ -49,23 +81,34 @@ impl CNF {
         self.clauses.get_from_db(id)
     }
 
+    /// Clauses as they were given, before any simplification, in order.
+    pub fn original_clauses(&self) -> impl Iterator<Item = &Clause> {
+        self.clauses.original_clauses()
+    }
+
     pub fn add_clause(&mut self, clause: Clause) {
         self.clauses.add(clause);
     }
 
-    pub fn clauses(&self) -> clauses::ClauseIter {
+    /// Current clauses with at least two literals.
+    pub fn clauses(&self) -> impl Iterator<Item = &[Literal]> {
         self.clauses.clauses()
     }
 
-    pub fn unit_clauses(&self) -> clauses::ClauseIter {
+    /// Current clauses, including unit and empty ones.
+    pub fn all_clauses(&self) -> impl Iterator<Item = &[Literal]> {
+        self.clauses.iter()
+    }
+
+    pub fn unit_clauses(&self) -> impl Iterator<Item = &[Literal]> {
         self.clauses.unit_clauses()
     }
 
-    pub fn empty_clauses(&self) -> clauses::ClauseIter {
+    pub fn empty_clauses(&self) -> impl Iterator<Item = &[Literal]> {
         self.clauses.empty_clauses()
     }
 
-    pub fn literals(&self) -> impl Iterator<Item = &Literal> {
+    pub fn literals(&self) -> impl Iterator<Item = Literal> + '_ {
         self.clauses.literals()
     }
 
@@ -82,9 +125,9 @@ impl CNF {
         let mut found = HashMap::with_capacity(self.clauses.len_literals());
         let mut impure = HashSet::new();
         for lit in self.literals() {
-            match found.get(lit.variable()) {
+            match found.get(&lit.variable()) {
                 Some(sign) if lit.is_negated() == *sign => {
-                    impure.insert(lit.clone());
+                    impure.insert(lit);
                     impure.insert(lit.negated());
                 }
                 Some(_) => (),
@@ -101,29 +144,39 @@ impl CNF {
         self.clauses.remove_clauses_with(literal);
         self.clauses.remove_literals(&literal.negated());
     }
+
+    /// The current state of simplification, to be restored by `rollback`.
+    pub fn checkpoint(&self) -> Checkpoint {
+        self.clauses.checkpoint()
+    }
+
+    /// Undo the simplifications made since `checkpoint`, in reverse order.
+    pub fn rollback(&mut self, checkpoint: Checkpoint) {
+        self.clauses.rollback(checkpoint);
+    }
 }
 
+/// A clause, kept as a sorted list of distinct literals.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Clause {
-    literals: HashSet<Literal>,
+    literals: Vec<Literal>,
 }
 
 impl fmt::Display for Clause {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.write_str(&self.literals.iter().join(" ∨ "))
+        self.fmt_named(&NameTable::new(), f)
     }
 }
 
-impl str::FromStr for Clause {
-    type Err = ParseVariableError;
+impl DisplayNamed for Clause {
+    fn fmt_named(&self, names: &NameTable, f: &mut fmt::Formatter) -> fmt::Result {
+        self.literals[..].fmt_named(names, f)
+    }
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let literals = s
-            .split(" ∨ ")
-            .flat_map(|sub| sub.split(" \\/ "))
-            .map(|l| l.parse())
-            .collect::<Result<HashSet<_>, _>>()?;
-        Ok(Clause { literals })
+impl DisplayNamed for [Literal] {
+    fn fmt_named(&self, names: &NameTable, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.iter().map(|l| names.display(l)).join(" ∨ "))
     }
 }
 
@@ -132,9 +185,10 @@ impl Clause {
     where
         T: IntoIterator<Item = Literal>,
     {
-        Clause {
-            literals: literals.into_iter().collect(),
-        }
+        let mut literals: Vec<_> = literals.into_iter().collect();
+        literals.sort_unstable();
+        literals.dedup();
+        Clause { literals }
     }
 
     pub fn literals(&self) -> impl Iterator<Item = &Literal> {
@@ -142,11 +196,13 @@ impl Clause {
     }
 
     pub fn remove_literal(&mut self, literal: &Literal) {
-        self.literals.remove(literal);
+        if let Ok(i) = self.literals.binary_search(literal) {
+            self.literals.remove(i);
+        }
     }
 
     pub fn has_literal(&self, literal: &Literal) -> bool {
-        self.literals.contains(literal)
+        self.literals.binary_search(literal).is_ok()
     }
 
     pub fn is_empty(&self) -> bool {
@@ -159,56 +215,31 @@ impl Clause {
 
     pub fn unit(&self) -> Option<&Literal> {
         if self.is_unit() {
-            Some(self.literals.iter().next().unwrap())
+            self.literals.first()
         } else {
             None
         }
     }
 }
 
-#[derive(PartialEq, Eq, Clone, Hash, Debug)]
-pub struct Literal {
-    variable: Variable,
-    negated: bool,
-}
-
-impl ops::Deref for Literal {
-    type Target = Variable;
-    fn deref(&self) -> &Variable {
-        &self.variable
-    }
-}
+/// A literal encoded as `2v` (positive) or `2v + 1` (negative) for variable index `v`.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash, Debug)]
+pub struct Literal(u32);
 
 impl fmt::Display for Literal {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.pad(&if self.negated {
-            format!("¬{}", self.variable)
-        } else {
-            format!("{}", self.variable)
-        })
+        self.fmt_named(&NameTable::new(), f)
     }
 }
 
-impl str::FromStr for Literal {
-    type Err = ParseVariableError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Some(v) = s.strip_prefix('¬') {
-            Ok(Literal {
-                variable: v.parse()?,
-                negated: true,
-            })
-        } else if let Some(v) = s.strip_prefix('!') {
-            Ok(Literal {
-                variable: v.parse()?,
-                negated: true,
-            })
+impl DisplayNamed for Literal {
+    fn fmt_named(&self, names: &NameTable, f: &mut fmt::Formatter) -> fmt::Result {
+        let variable = self.variable();
+        f.pad(&if self.is_negated() {
+            format!("¬{}", names.display(&variable))
         } else {
-            Ok(Literal {
-                variable: s.parse()?,
-                negated: false,
-            })
-        }
+            format!("{}", names.display(&variable))
+        })
     }
 }
 
@@ -218,39 +249,72 @@ impl From<Variable> for Literal {
     }
 }
 
+impl Word for Literal {
+    fn from_word(word: u32) -> Literal {
+        Literal(word)
+    }
+
+    fn word(self) -> u32 {
+        self.0
+    }
+}
+
+impl ops::Not for Literal {
+    type Output = Literal;
+    fn not(self) -> Literal {
+        Literal(self.0 ^ 1)
+    }
+}
+
 impl Literal {
     pub fn new(variable: Variable, is_negated: bool) -> Literal {
-        Literal {
-            variable,
-            negated: is_negated,
-        }
+        Literal(variable.0 << 1 | is_negated as u32)
     }
 
-    pub fn variable(&self) -> &Variable {
-        &self.variable
+    pub fn variable(self) -> Variable {
+        Variable(self.0 >> 1)
     }
 
-    pub fn is_negated(&self) -> bool {
-        self.negated
+    pub fn is_negated(self) -> bool {
+        self.0 & 1 == 1
     }
 
     pub fn negate(&mut self) {
-        self.negated = !self.negated;
+        self.0 ^= 1;
     }
 
-    pub fn negated(&self) -> Literal {
-        let mut c = self.clone();
-        c.negate();
-        c
+    pub fn negated(self) -> Literal {
+        !self
+    }
+
+    /// Dense index of the literal, usable to index arrays by literal.
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    pub fn from_index(index: usize) -> Literal {
+        Literal(index as u32)
     }
 }
 
-#[derive(PartialEq, Eq, Hash, Clone, Debug)]
-pub struct Variable(String);
+/// A variable, identified by a dense index.
+///
+/// Names are kept in the `NameTable` of the CNF and only used for display and parsing.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug)]
+pub struct Variable(u32);
 
 impl fmt::Display for Variable {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.pad(&self.0)
+        self.fmt_named(&NameTable::new(), f)
+    }
+}
+
+impl DisplayNamed for Variable {
+    fn fmt_named(&self, names: &NameTable, f: &mut fmt::Formatter) -> fmt::Result {
+        match names.name(*self) {
+            Some(name) => f.pad(name),
+            None => f.pad(&format!("#{}", self.0)),
+        }
     }
 }
 
@@ -272,23 +336,57 @@ impl fmt::Display for ParseVariableError {
 
 impl std::error::Error for ParseVariableError {}
 
-impl str::FromStr for Variable {
-    type Err = ParseVariableError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.is_empty() || s.chars().any(|c| !char::is_alphanumeric(c)) {
-            return Err(ParseVariableError {
-                kind: ParseVariableErrorKind::InvalidVariable,
-            });
-        }
+impl Variable {
+    /// Dense index of the variable, usable to index arrays by variable.
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
 
-        Ok(Variable(s.to_string()))
+    /// Inverse of `index`; the index must belong to an existing variable.
+    pub fn from_index(index: usize) -> Variable {
+        Variable(index as u32)
     }
 }
 
-impl Variable {
-    /// `Variable::fresh(id)` is fresh iff `id` is unique
-    pub fn fresh(id: usize) -> Variable {
-        Variable(format!("x_{}", id))
+#[cfg(test)]
+mod tests {
+    use super::{Literal, CNF};
+
+    fn sorted_clauses(cnf: &CNF) -> Vec<Vec<Literal>> {
+        let mut clauses: Vec<Vec<_>> = cnf
+            .all_clauses()
+            .map(|c| {
+                let mut c = c.to_vec();
+                c.sort();
+                c
+            })
+            .collect();
+        clauses.sort();
+        clauses
+    }
+
+    #[test]
+    fn test_rollback() {
+        let mut cnf: CNF = "A ∨ B ∧ ¬A ∨ C ∧ ¬B ∨ ¬C".parse().unwrap();
+        let original = sorted_clauses(&cnf);
+
+        let mut literal = |s| cnf.names_mut().parse_literal(s).unwrap();
+        let (a, c, not_b) = (literal("A"), literal("C"), literal("¬B"));
+
+        let checkpoint = cnf.checkpoint();
+        cnf.simplify_true_literal(&a);
+        let simplified = sorted_clauses(&cnf);
+        assert_eq!(simplified.len(), 2);
+
+        let inner = cnf.checkpoint();
+        cnf.simplify_true_literal(&c);
+        assert_eq!(sorted_clauses(&cnf), vec![vec![not_b]]);
+        let clause = cnf.names_mut().parse_clause("B ∨ D").unwrap();
+        cnf.add_clause(clause);
+
+        cnf.rollback(inner);
+        assert_eq!(sorted_clauses(&cnf), simplified);
+        cnf.rollback(checkpoint);
+        assert_eq!(sorted_clauses(&cnf), original);
     }
 }
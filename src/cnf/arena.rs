@@ -0,0 +1,294 @@
+use std::ops;
+
+use super::clause_id::ClauseID;
+
+/// Literal types that can be stored in a `ClauseArena`.
+///
+/// Header words share the memory of literals, so they are converted to and
+/// from plain `u32` words.
+pub trait Word: Copy {
+    fn from_word(word: u32) -> Self;
+    fn word(self) -> u32;
+}
+
+/// Offset of a clause in a `ClauseArena`.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Hash)]
+pub struct ClauseRef(u32);
+
+const CAPACITY: usize = 0;
+const SIZE: usize = 1;
+const FLAGS: usize = 2;
+const ACTIVITY: usize = 3;
+const ID: usize = 4;
+const HEADER_LEN: usize = 5;
+
+const LEARNED: u32 = 1;
+const USED: u32 = 1 << 1;
+const REMOVED: u32 = 1 << 2;
+const LBD_SHIFT: u32 = 3;
+
+/// Contiguous storage of clauses.
+///
+/// Each clause is a header followed by its literals. The header holds the
+/// number of allocated literals, the current size, the learned/used/removed
+/// flags together with the LBD, the activity and the clause ID. Removed
+/// clauses leave garbage behind until `collect_garbage` compacts the arena.
+#[derive(Debug, Clone)]
+pub struct ClauseArena<L> {
+    memory: Vec<L>,
+    wasted: usize,
+}
+
+impl<L: Word> ops::Index<ClauseRef> for ClauseArena<L> {
+    type Output = [L];
+    fn index(&self, cref: ClauseRef) -> &[L] {
+        let start = cref.0 as usize + HEADER_LEN;
+        &self.memory[start..start + self.len(cref)]
+    }
+}
+
+impl<L: Word> ops::IndexMut<ClauseRef> for ClauseArena<L> {
+    fn index_mut(&mut self, cref: ClauseRef) -> &mut [L] {
+        let start = cref.0 as usize + HEADER_LEN;
+        let end = start + self.len(cref);
+        &mut self.memory[start..end]
+    }
+}
+
+impl<L: Word> ClauseArena<L> {
+    pub fn new() -> Self {
+        ClauseArena {
+            memory: Vec::new(),
+            wasted: 0,
+        }
+    }
+
+    pub fn alloc(&mut self, id: ClauseID, literals: &[L], learned: bool) -> ClauseRef {
+        let cref = ClauseRef(self.memory.len() as u32);
+        let flags = if learned { LEARNED } else { 0 };
+        self.memory.extend(
+            [
+                literals.len() as u32,
+                literals.len() as u32,
+                flags,
+                0f32.to_bits(),
+                id.index() as u32,
+            ]
+            .iter()
+            .map(|&w| L::from_word(w)),
+        );
+        self.memory.extend_from_slice(literals);
+        cref
+    }
+
+    fn header(&self, cref: ClauseRef, field: usize) -> u32 {
+        self.memory[cref.0 as usize + field].word()
+    }
+
+    fn set_header(&mut self, cref: ClauseRef, field: usize, word: u32) {
+        self.memory[cref.0 as usize + field] = L::from_word(word);
+    }
+
+    fn flag(&self, cref: ClauseRef, flag: u32) -> bool {
+        self.header(cref, FLAGS) & flag != 0
+    }
+
+    fn set_flag(&mut self, cref: ClauseRef, flag: u32, on: bool) {
+        let flags = self.header(cref, FLAGS);
+        let flags = if on { flags | flag } else { flags & !flag };
+        self.set_header(cref, FLAGS, flags);
+    }
+
+    pub fn len(&self, cref: ClauseRef) -> usize {
+        self.header(cref, SIZE) as usize
+    }
+
+    pub fn is_empty(&self, cref: ClauseRef) -> bool {
+        self.len(cref) == 0
+    }
+
+    /// Change the size of the clause within the literals it was allocated with.
+    ///
+    /// Literals beyond the new size are kept, so shrinking can be undone.
+    pub fn set_len(&mut self, cref: ClauseRef, len: usize) {
+        assert!(len <= self.header(cref, CAPACITY) as usize);
+        self.set_header(cref, SIZE, len as u32);
+    }
+
+    pub fn id(&self, cref: ClauseRef) -> ClauseID {
+        ClauseID::from_index(self.header(cref, ID) as usize)
+    }
+
+    pub fn is_learned(&self, cref: ClauseRef) -> bool {
+        self.flag(cref, LEARNED)
+    }
+
+    pub fn is_used(&self, cref: ClauseRef) -> bool {
+        self.flag(cref, USED)
+    }
+
+    pub fn set_used(&mut self, cref: ClauseRef, used: bool) {
+        self.set_flag(cref, USED, used);
+    }
+
+    pub fn is_removed(&self, cref: ClauseRef) -> bool {
+        self.flag(cref, REMOVED)
+    }
+
+    pub fn lbd(&self, cref: ClauseRef) -> usize {
+        (self.header(cref, FLAGS) >> LBD_SHIFT) as usize
+    }
+
+    pub fn set_lbd(&mut self, cref: ClauseRef, lbd: usize) {
+        let flags = self.header(cref, FLAGS) & ((1 << LBD_SHIFT) - 1);
+        self.set_header(cref, FLAGS, flags | (lbd as u32) << LBD_SHIFT);
+    }
+
+    pub fn activity(&self, cref: ClauseRef) -> f32 {
+        f32::from_bits(self.header(cref, ACTIVITY))
+    }
+
+    pub fn set_activity(&mut self, cref: ClauseRef, activity: f32) {
+        self.set_header(cref, ACTIVITY, activity.to_bits());
+    }
+
+    /// Mark the clause as removed; its memory is reclaimed by `collect_garbage`.
+    pub fn remove(&mut self, cref: ClauseRef) {
+        debug_assert!(!self.is_removed(cref));
+        self.set_flag(cref, REMOVED, true);
+        self.wasted += HEADER_LEN + self.header(cref, CAPACITY) as usize;
+    }
+
+    /// Undo `remove`, as long as no garbage was collected in between.
+    pub fn restore(&mut self, cref: ClauseRef) {
+        debug_assert!(self.is_removed(cref));
+        self.set_flag(cref, REMOVED, false);
+        self.wasted -= HEADER_LEN + self.header(cref, CAPACITY) as usize;
+    }
+
+    /// Number of words, including garbage.
+    pub fn size(&self) -> usize {
+        self.memory.len()
+    }
+
+    /// Number of words taken by removed clauses.
+    pub fn wasted(&self) -> usize {
+        self.wasted
+    }
+
+    /// Clauses that are not removed, in order of allocation.
+    pub fn refs(&self) -> Refs<'_, L> {
+        Refs {
+            arena: self,
+            offset: 0,
+        }
+    }
+
+    /// Move the clauses that are not removed to the front of the memory.
+    ///
+    /// References to the clauses must be updated through the returned `Relocation`.
+    pub fn collect_garbage(&mut self) -> Relocation {
+        let mut moves = Vec::new();
+        let mut offset = 0;
+        let mut to = 0;
+        while offset < self.memory.len() {
+            let cref = ClauseRef(offset as u32);
+            let len = HEADER_LEN + self.header(cref, CAPACITY) as usize;
+            if !self.is_removed(cref) {
+                self.memory.copy_within(offset..offset + len, to);
+                moves.push((cref, ClauseRef(to as u32)));
+                to += len;
+            }
+            offset += len;
+        }
+        self.memory.truncate(to);
+        self.wasted = 0;
+        Relocation { moves }
+    }
+}
+
+impl<L: Word> Default for ClauseArena<L> {
+    fn default() -> Self {
+        ClauseArena::new()
+    }
+}
+
+pub struct Refs<'a, L> {
+    arena: &'a ClauseArena<L>,
+    offset: usize,
+}
+
+impl<'a, L: Word> Iterator for Refs<'a, L> {
+    type Item = ClauseRef;
+    fn next(&mut self) -> Option<ClauseRef> {
+        while self.offset < self.arena.memory.len() {
+            let cref = ClauseRef(self.offset as u32);
+            self.offset += HEADER_LEN + self.arena.header(cref, CAPACITY) as usize;
+            if !self.arena.is_removed(cref) {
+                return Some(cref);
+            }
+        }
+        None
+    }
+}
+
+/// New locations of the clauses moved by `ClauseArena::collect_garbage`.
+pub struct Relocation {
+    moves: Vec<(ClauseRef, ClauseRef)>,
+}
+
+impl Relocation {
+    /// The new location of a clause, or `None` if it was removed.
+    pub fn get(&self, cref: ClauseRef) -> Option<ClauseRef> {
+        self.moves
+            .binary_search_by_key(&cref, |&(from, _)| from)
+            .ok()
+            .map(|i| self.moves[i].1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClauseArena, Word};
+    use crate::cnf::ClauseIDGenerator;
+
+    impl Word for u32 {
+        fn from_word(word: u32) -> u32 {
+            word
+        }
+
+        fn word(self) -> u32 {
+            self
+        }
+    }
+
+    #[test]
+    fn test_collect_garbage() {
+        let mut ids = ClauseIDGenerator::new();
+        let mut arena = ClauseArena::new();
+        let c1 = arena.alloc(ids.next(), &[1, 2, 3], false);
+        let c2 = arena.alloc(ids.next(), &[4, 5], true);
+        let c3 = arena.alloc(ids.next(), &[6, 7, 8], true);
+        arena.set_lbd(c3, 2);
+        arena.set_activity(c3, 1.5);
+        arena.set_len(c1, 2);
+
+        arena.remove(c2);
+        assert_eq!(arena.refs().collect::<Vec<_>>(), vec![c1, c3]);
+
+        let relocation = arena.collect_garbage();
+        assert_eq!(arena.wasted(), 0);
+        assert_eq!(relocation.get(c1), Some(c1));
+        assert_eq!(relocation.get(c2), None);
+
+        let c3 = relocation.get(c3).unwrap();
+        assert_eq!(&arena[c3], &[6, 7, 8]);
+        assert_eq!(arena.lbd(c3), 2);
+        assert_eq!(arena.activity(c3), 1.5);
+        assert!(arena.is_learned(c3));
+        assert_eq!(arena.id(c3).index(), 2);
+
+        arena.set_len(c1, 3);
+        assert_eq!(&arena[c1], &[1, 2, 3]);
+    }
+}
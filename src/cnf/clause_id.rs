@@ -1,6 +1,6 @@
 use std::fmt;
 
-#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug)]
 pub struct ClauseID(usize);
 
 impl ClauseID {
@@ -11,6 +11,15 @@ impl ClauseID {
     pub(self) fn next(self) -> ClauseID {
         ClauseID(self.0 + 1)
     }
+
+    /// Position of the clause in order of generation, starting from 0.
+    pub fn index(self) -> usize {
+        self.0
+    }
+
+    pub(super) fn from_index(index: usize) -> ClauseID {
+        ClauseID(index)
+    }
 }
 
 impl fmt::Display for ClauseID {
@@ -37,3 +46,9 @@ impl ClauseIDGenerator {
         id
     }
 }
+
+impl Default for ClauseIDGenerator {
+    fn default() -> Self {
+        ClauseIDGenerator::new()
+    }
+}
@@ -1,20 +1,31 @@
-use std::collections::{
-    hash_map::{self, Values},
-    HashMap, HashSet,
-};
-use std::iter::{Chain, FromIterator};
+use std::collections::HashSet;
+use std::iter::FromIterator;
 use std::string;
 
+use super::arena::{ClauseArena, ClauseRef, Word};
 use super::clause_id::{ClauseID, ClauseIDGenerator};
 use crate::cnf::{Clause, Literal};
 
 use itertools::Itertools;
 use log::error;
 
+/// Simplifications applied to the clauses, in order.
+///
+/// `removed_clauses` and `removed_literals` are applied to clauses added
+/// later, and `changes` are undone by `Clauses::rollback`.
 #[derive(Debug, Clone)]
 struct History {
     removed_clauses: Vec<Literal>,
     removed_literals: Vec<Literal>,
+    changes: Vec<Change>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Change {
+    Added(ClauseRef),
+    Removed(ClauseRef),
+    /// The clause lost the literal now right past its end.
+    Shrank(ClauseRef),
 }
 
 impl History {
@@ -22,104 +33,59 @@ impl History {
         History {
             removed_clauses: Vec::new(),
             removed_literals: Vec::new(),
+            changes: Vec::new(),
         }
     }
 }
 
+/// A point in the history of the clauses, to be restored by `rollback`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint {
+    removed_clauses: usize,
+    removed_literals: usize,
+    changes: usize,
+}
+
+/// Clauses of a CNF, simplified in place.
+///
+/// Clauses as they were added are kept in `db`, indexed by their IDs. Their
+/// simplified copies live in an arena, where removing a literal shrinks the
+/// clause and removing a clause only marks it as removed.
 #[derive(Debug, Clone)]
 pub struct Clauses {
-    db: HashMap<ClauseID, Clause>,
-    clauses: HashMap<ClauseID, Clause>,
-    unit_clauses: HashMap<ClauseID, Clause>,
-    empty_clauses: HashMap<ClauseID, Clause>,
+    db: Vec<Clause>,
+    arena: ClauseArena<Literal>,
+    num_clauses: usize,
     table: Table,
     id_gen: ClauseIDGenerator,
     history: History,
 }
 
-impl<'a> IntoIterator for &'a Clauses {
-    type Item = &'a Clause;
-    #[allow(clippy::type_complexity)]
-    type IntoIter = Chain<
-        Chain<Values<'a, ClauseID, Clause>, Values<'a, ClauseID, Clause>>,
-        Values<'a, ClauseID, Clause>,
-    >;
-    fn into_iter(self) -> Self::IntoIter {
-        self.clauses
-            .values()
-            .chain(self.unit_clauses.values())
-            .chain(self.empty_clauses.values())
-    }
-}
-
 impl FromIterator<Clause> for Clauses {
     fn from_iter<T>(cls: T) -> Clauses
     where
         T: IntoIterator<Item = Clause>,
     {
-        let iter = cls.into_iter();
-
-        let (lower, upper) = iter.size_hint();
-        let initial_capacity = upper.unwrap_or(lower);
-        let mut db = HashMap::with_capacity(initial_capacity);
-        let mut clauses = HashMap::with_capacity(initial_capacity);
-        let mut empty_clauses = HashMap::with_capacity(initial_capacity);
-        let mut unit_clauses = HashMap::with_capacity(initial_capacity);
-        let mut table = Table::with_capacity(initial_capacity);
-
-        let mut id_gen = ClauseIDGenerator::new();
-
-        for c in iter {
-            let id = id_gen.next();
-            for l in c.literals() {
-                table.register(l, id);
-            }
-
-            db.insert(id, c.clone());
-            if c.is_empty() {
-                empty_clauses.insert(id, c);
-            } else if c.is_unit() {
-                unit_clauses.insert(id, c);
-            } else {
-                clauses.insert(id, c);
-            }
-        }
-
-        let cls = Clauses {
-            db,
-            clauses,
-            empty_clauses,
-            unit_clauses,
-            table,
-            id_gen,
+        let mut clauses = Clauses {
+            db: Vec::new(),
+            arena: ClauseArena::new(),
+            num_clauses: 0,
+            table: Table::new(),
+            id_gen: ClauseIDGenerator::new(),
             history: History::new(),
         };
-        debug_assert!(cls.check_sanity());
-        cls
-    }
-}
-
-pub struct ClauseIter<'a> {
-    inner: hash_map::Iter<'a, ClauseID, Clause>,
-}
-
-impl<'a> Iterator for ClauseIter<'a> {
-    type Item = &'a Clause;
-    fn next(&mut self) -> Option<Self::Item> {
-        self.inner.next().map(|(_, c)| c)
-    }
-}
+        for c in cls {
+            clauses.add(c);
+        }
 
-impl<'a> ClauseIter<'a> {
-    pub fn with_id(self) -> impl Iterator<Item = (ClauseID, &'a Clause)> {
-        self.inner.map(|(id, c)| (*id, c))
+        debug_assert!(clauses.check_sanity());
+        clauses
     }
 }
 
 impl Clauses {
     fn check_sanity(&self) -> bool {
-        let literals_from_clauses: HashSet<_> =
-            self.into_iter().flat_map(|c| c.literals()).collect();
+        let literals_from_clauses: HashSet<_> = self.iter().flatten().copied().collect();
         let literals_from_table: HashSet<_> = self.literals().collect();
         if literals_from_clauses != literals_from_table {
             error!(
@@ -130,57 +96,20 @@ impl Clauses {
             return false;
         }
 
-        let all_clauses_prop = self.clauses.values().all(|c| !c.is_unit() && !c.is_empty());
-        if !all_clauses_prop {
-            error!(
-                "malformed clauses: {}",
-                display_as_set(self.clauses.values())
-            );
-            return false;
-        }
-
-        let all_unit_clauses_prop = self.unit_clauses.values().all(|c| c.is_unit());
-        if !all_unit_clauses_prop {
-            error!(
-                "malformed unit clauses: {}",
-                display_as_set(self.unit_clauses.values())
-            );
-            return false;
-        }
-
-        let all_empty_clauses_prop = self.empty_clauses.values().all(|c| c.is_empty());
-        if !all_empty_clauses_prop {
-            error!(
-                "malformed empty _clauses: {}",
-                display_as_set(self.empty_clauses.values())
-            );
-            return false;
+        for cref in self.arena.refs() {
+            for l in &self.arena[cref] {
+                if !self.table.ids(l).contains(&cref) {
+                    error!("clause {:?} is not registered under {}", cref, l);
+                    return false;
+                }
+            }
         }
 
-        let clause_ids: HashSet<_> = self.clauses.keys().collect();
-        let unit_clause_ids = self.unit_clauses.keys().collect();
-        let empty_clause_ids = self.empty_clauses.keys().collect();
-        if !clause_ids.is_disjoint(&unit_clause_ids) {
-            error!(
-                "clause ids and unit clause ids overlap: {} vs {}",
-                display_as_set(self.clauses.keys()),
-                display_as_set(self.unit_clauses.keys())
-            );
-            return false;
-        }
-        if !unit_clause_ids.is_disjoint(&empty_clause_ids) {
-            error!(
-                "unit clause ids and empty clause ids overlap: {} vs {}",
-                display_as_set(self.unit_clauses.keys()),
-                display_as_set(self.empty_clauses.keys())
-            );
-            return false;
-        }
-        if !empty_clause_ids.is_disjoint(&clause_ids) {
+        let num_clauses = self.arena.refs().count();
+        if num_clauses != self.num_clauses {
             error!(
-                "empty clause ids and clause ids overlap: {} vs {}",
-                display_as_set(self.empty_clauses.keys()),
-                display_as_set(self.clauses.keys())
+                "clause count mismatch: {} vs {}",
+                num_clauses, self.num_clauses
             );
             return false;
         }
@@ -188,71 +117,70 @@ impl Clauses {
         true
     }
 
-    pub fn get(&self, id: ClauseID) -> Option<&Clause> {
-        self.clauses
-            .get(&id)
-            .or_else(|| self.unit_clauses.get(&id))
-            .or_else(|| self.empty_clauses.get(&id))
+    pub fn get_from_db(&self, id: ClauseID) -> Option<&Clause> {
+        self.db.get(id.index())
     }
 
-    pub fn get_from_db(&self, id: ClauseID) -> Option<&Clause> {
-        self.db.get(&id)
+    /// Clauses as they were added, in order of addition.
+    pub fn original_clauses(&self) -> impl Iterator<Item = &Clause> {
+        self.db.iter()
     }
 
-    pub fn most_occurred_literal(&self) -> Option<&Literal> {
+    pub fn most_occurred_literal(&self) -> Option<Literal> {
         self.literals().max_by_key(|l| self.table.ids(l).len())
     }
 
-    pub fn add(&mut self, mut clause: Clause) {
+    pub fn add(&mut self, clause: Clause) {
         let id = self.id_gen.next();
-        self.db.insert(id, clause.clone());
+        let literals: Vec<_> = clause.literals().copied().collect();
+        self.db.push(clause);
 
-        for l in &self.history.removed_literals {
-            clause.remove_literal(l);
+        let cref = self.arena.alloc(id, &literals, false);
+        for l in &literals {
+            self.table.register(l, cref);
         }
+        self.num_clauses += 1;
+        self.history.changes.push(Change::Added(cref));
 
-        for l in &self.history.removed_clauses {
-            if clause.has_literal(l) {
-                return;
+        for i in 0..self.history.removed_literals.len() {
+            let literal = self.history.removed_literals[i];
+            if self.arena[cref].contains(&literal) {
+                self.remove_literal(cref, &literal);
             }
         }
-
-        for l in clause.literals() {
-            self.table.register(l, id);
-        }
-
-        if clause.is_empty() {
-            self.empty_clauses.insert(id, clause);
-        } else if clause.is_unit() {
-            self.unit_clauses.insert(id, clause);
-        } else {
-            self.clauses.insert(id, clause);
+        if self
+            .history
+            .removed_clauses
+            .iter()
+            .any(|l| self.arena[cref].contains(l))
+        {
+            self.remove_clause(cref);
         }
     }
 
     pub fn is_empty(&self) -> bool {
-        self.clauses.is_empty() && self.unit_clauses.is_empty() && self.empty_clauses.is_empty()
+        self.num_clauses == 0
     }
 
-    pub fn clauses(&self) -> ClauseIter {
-        ClauseIter {
-            inner: self.clauses.iter(),
-        }
+    /// All clauses, including unit and empty ones.
+    pub fn iter(&self) -> impl Iterator<Item = &[Literal]> {
+        self.arena.refs().map(move |cref| &self.arena[cref])
     }
 
-    pub fn unit_clauses(&self) -> ClauseIter {
-        ClauseIter {
-            inner: self.unit_clauses.iter(),
-        }
+    /// Clauses with at least two literals.
+    pub fn clauses(&self) -> impl Iterator<Item = &[Literal]> {
+        self.iter().filter(|c| c.len() >= 2)
     }
 
-    pub fn empty_clauses(&self) -> ClauseIter {
-        ClauseIter {
-            inner: self.empty_clauses.iter(),
-        }
+    pub fn unit_clauses(&self) -> impl Iterator<Item = &[Literal]> {
+        self.iter().filter(|c| c.len() == 1)
     }
 
-    pub fn literals(&self) -> impl Iterator<Item = &Literal> {
+    pub fn empty_clauses(&self) -> impl Iterator<Item = &[Literal]> {
+        self.iter().filter(|c| c.is_empty())
+    }
+
+    pub fn literals(&self) -> impl Iterator<Item = Literal> + '_ {
         self.table.literals()
     }
 
@@ -260,122 +188,148 @@ impl Clauses {
         self.table.len()
     }
 
-    fn contains_id(&self, id: ClauseID) -> bool {
-        self.clauses.contains_key(&id)
-            || self.unit_clauses.contains_key(&id)
-            || self.empty_clauses.contains_key(&id)
+    fn remove_clause(&mut self, cref: ClauseRef) {
+        for l in &self.arena[cref] {
+            self.table.unregister(l, cref);
+        }
+        self.arena.remove(cref);
+        self.num_clauses -= 1;
+        self.history.changes.push(Change::Removed(cref));
     }
 
-    fn remove_clause_by_id(&mut self, id: ClauseID) -> Option<Clause> {
-        let res = self
-            .clauses
-            .remove(&id)
-            .or_else(|| self.unit_clauses.remove(&id))
-            .or_else(|| self.empty_clauses.remove(&id));
+    /// Move the literal past the end of the clause, where it is kept for rolling back.
+    fn remove_literal(&mut self, cref: ClauseRef, literal: &Literal) {
+        let literals = &mut self.arena[cref];
+        let last = literals.len() - 1;
+        let i = literals.iter().position(|l| l == literal).unwrap();
+        literals.swap(i, last);
+        self.arena.set_len(cref, last);
+        self.table.unregister(literal, cref);
+        self.history.changes.push(Change::Shrank(cref));
+    }
 
-        if let Some(c) = &res {
-            for l in c.literals() {
-                self.table.unregister(l, id);
-            }
+    pub fn remove_clauses_with(&mut self, literal: &Literal) {
+        self.history.removed_clauses.push(*literal);
+
+        for cref in self.table.ids(literal).to_vec() {
+            assert!(!self.arena.is_removed(cref));
+            self.remove_clause(cref);
         }
 
+        debug_assert!(self.table.ids(literal).is_empty());
         debug_assert!(self.check_sanity());
-
-        res
     }
 
-    pub fn remove_clauses_with(&mut self, literal: &Literal) {
-        self.history.removed_clauses.push(literal.clone());
+    pub fn remove_literals(&mut self, literal: &Literal) {
+        self.history.removed_literals.push(*literal);
 
-        for id in self.table.ids(literal) {
-            assert!(self.contains_id(id));
-            self.remove_clause_by_id(id);
+        for cref in self.table.ids(literal).to_vec() {
+            assert!(!self.arena.is_removed(cref));
+            self.remove_literal(cref, literal);
         }
 
-        // self.table.unregister_all(literal);
-        debug_assert!(self.table.literals().find(|l| l == &literal).is_none());
+        debug_assert!(self.table.ids(literal).is_empty());
         debug_assert!(self.check_sanity());
     }
 
-    #[allow(clippy::map_entry)]
-    pub fn remove_literals(&mut self, literal: &Literal) {
-        self.history.removed_literals.push(literal.clone());
-
-        for id in self.table.ids(literal) {
-            assert!(self.contains_id(id));
-
-            assert!(!self.empty_clauses.contains_key(&id));
-            if self.unit_clauses.contains_key(&id) {
-                debug_assert_eq!(
-                    self.unit_clauses
-                        .get(&id)
-                        .unwrap()
-                        .literals()
-                        .collect::<Vec<_>>(),
-                    vec![literal]
-                );
-                let mut c = self.unit_clauses.remove(&id).unwrap();
-                c.remove_literal(literal);
-                debug_assert!(c.is_empty());
-                self.empty_clauses.insert(id, c);
-            } else {
-                let c = self.clauses.get_mut(&id).unwrap();
-                c.remove_literal(literal);
-                if c.is_unit() {
-                    let c = self.clauses.remove(&id).unwrap();
-                    self.unit_clauses.insert(id, c);
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            removed_clauses: self.history.removed_clauses.len(),
+            removed_literals: self.history.removed_literals.len(),
+            changes: self.history.changes.len(),
+        }
+    }
+
+    /// Undo the simplifications made since the checkpoint, latest first.
+    ///
+    /// Clauses added in the meantime are dropped again, but stay among the
+    /// original clauses.
+    pub fn rollback(&mut self, checkpoint: Checkpoint) {
+        while self.history.changes.len() > checkpoint.changes {
+            match self.history.changes.pop().unwrap() {
+                Change::Added(cref) => {
+                    for l in &self.arena[cref] {
+                        self.table.unregister(l, cref);
+                    }
+                    self.arena.remove(cref);
+                    self.num_clauses -= 1;
+                }
+                Change::Removed(cref) => {
+                    self.arena.restore(cref);
+                    for l in &self.arena[cref] {
+                        self.table.register(l, cref);
+                    }
+                    self.num_clauses += 1;
+                }
+                Change::Shrank(cref) => {
+                    let len = self.arena.len(cref);
+                    self.arena.set_len(cref, len + 1);
+                    self.table.register(&self.arena[cref][len], cref);
                 }
             }
         }
-        self.table.unregister_all(literal);
+        self.history
+            .removed_clauses
+            .truncate(checkpoint.removed_clauses);
+        self.history
+            .removed_literals
+            .truncate(checkpoint.removed_literals);
 
         debug_assert!(self.check_sanity());
     }
 }
 
+/// Clauses containing each literal, indexed by literal.
 #[derive(Debug, Clone)]
 pub struct Table {
-    inner: HashMap<Literal, HashSet<ClauseID>>,
+    inner: Vec<Vec<ClauseRef>>,
+    len: usize,
 }
 
 impl Table {
-    pub fn with_capacity(size: usize) -> Table {
+    pub fn new() -> Table {
         Table {
-            inner: HashMap::with_capacity(size),
+            inner: Vec::new(),
+            len: 0,
         }
     }
 
-    pub fn register(&mut self, k: &Literal, v: ClauseID) {
-        self.inner
-            .entry(k.clone())
-            .or_insert_with(HashSet::new)
-            .insert(v);
-    }
-
-    pub fn unregister_all(&mut self, k: &Literal) {
-        self.inner.remove(k);
+    pub fn register(&mut self, k: &Literal, v: ClauseRef) {
+        if k.index() >= self.inner.len() {
+            self.inner.resize_with(k.index() + 1, Vec::new);
+        }
+        let ids = &mut self.inner[k.index()];
+        if ids.is_empty() {
+            self.len += 1;
+        }
+        ids.push(v);
     }
 
-    pub fn unregister(&mut self, k: &Literal, v: ClauseID) {
-        if let Some(s) = self.inner.get_mut(k) {
-            s.remove(&v);
-            if s.is_empty() {
-                self.inner.remove(k);
+    pub fn unregister(&mut self, k: &Literal, v: ClauseRef) {
+        if let Some(ids) = self.inner.get_mut(k.index()) {
+            if let Some(i) = ids.iter().position(|&cref| cref == v) {
+                ids.swap_remove(i);
+                if ids.is_empty() {
+                    self.len -= 1;
+                }
             }
         }
     }
 
-    // TODO: better API
-    pub fn ids(&self, k: &Literal) -> HashSet<ClauseID> {
-        self.inner.get(k).cloned().unwrap_or_default()
+    pub fn ids(&self, k: &Literal) -> &[ClauseRef] {
+        self.inner.get(k.index()).map_or(&[], Vec::as_slice)
     }
 
-    pub fn literals(&self) -> impl Iterator<Item = &Literal> {
-        self.inner.keys()
+    pub fn literals(&self) -> impl Iterator<Item = Literal> + '_ {
+        self.inner
+            .iter()
+            .enumerate()
+            .filter(|(_, ids)| !ids.is_empty())
+            .map(|(i, _)| Literal::from_word(i as u32))
     }
 
     pub fn len(&self) -> usize {
-        self.inner.len()
+        self.len
     }
 }
 
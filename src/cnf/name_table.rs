@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+use std::{error, fmt};
+
+use super::{Clause, Literal, ParseVariableError, ParseVariableErrorKind, Variable};
+
+/// Names of variables, indexed by variable index.
+///
+/// Each CNF owns the table of its variables, so that indices stay dense and
+/// tables of unrelated formulas do not grow each other.
+#[derive(Clone, Debug, Default)]
+pub struct NameTable {
+    names: Vec<String>,
+    indices: HashMap<String, Variable>,
+    /// Numbers of the variables read from DIMACS files, indexed by variable index.
+    dimacs_numbers: Vec<Option<u32>>,
+}
+
+impl NameTable {
+    pub fn new() -> Self {
+        NameTable::default()
+    }
+
+    /// The variable named `name`, allocating the next index if needed.
+    ///
+    /// The name is not checked for validity.
+    pub fn intern(&mut self, name: &str) -> Variable {
+        if let Some(&variable) = self.indices.get(name) {
+            return variable;
+        }
+
+        let variable = Variable::from_index(self.names.len());
+        self.names.push(name.to_owned());
+        self.indices.insert(name.to_owned(), variable);
+        variable
+    }
+
+    /// The variable standing for DIMACS variable `n`, named `v<n>`.
+    pub fn intern_dimacs(&mut self, n: u32) -> Variable {
+        let variable = self.intern(&format!("v{}", n));
+        if variable.index() >= self.dimacs_numbers.len() {
+            self.dimacs_numbers.resize(variable.index() + 1, None);
+        }
+        self.dimacs_numbers[variable.index()] = Some(n);
+        variable
+    }
+
+    /// The DIMACS number of a variable made by `intern_dimacs`.
+    ///
+    /// Variables that are only named like DIMACS ones, such as `v3` in an
+    /// expression, have no number.
+    pub fn dimacs_number(&self, variable: Variable) -> Option<u32> {
+        self.dimacs_numbers.get(variable.index()).copied().flatten()
+    }
+
+    pub fn get(&self, name: &str) -> Option<Variable> {
+        self.indices.get(name).copied()
+    }
+
+    pub fn name(&self, variable: Variable) -> Option<&str> {
+        self.names.get(variable.index()).map(String::as_str)
+    }
+
+    /// Number of named variables, whose indices are `0..len`.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Add the names of `other`, which must agree on the variables named in both.
+    ///
+    /// On a conflict the table is left unchanged.
+    pub fn merge(&mut self, other: &NameTable) -> Result<(), NameConflict> {
+        for (index, name) in other.names.iter().enumerate().take(self.names.len()) {
+            let variable = Variable::from_index(index);
+            let numbers = (self.dimacs_number(variable), other.dimacs_number(variable));
+            if self.names[index] != *name || numbers.0 != numbers.1 {
+                return Err(NameConflict {
+                    variable,
+                    ours: self.names[index].clone(),
+                    theirs: name.clone(),
+                });
+            }
+        }
+
+        for index in self.names.len()..other.names.len() {
+            let variable = Variable::from_index(index);
+            match other.dimacs_number(variable) {
+                Some(n) => self.intern_dimacs(n),
+                None => self.intern(&other.names[index]),
+            };
+        }
+        Ok(())
+    }
+
+    pub fn parse_variable(&mut self, s: &str) -> Result<Variable, ParseVariableError> {
+        if s.is_empty() || s.chars().any(|c| !char::is_alphanumeric(c)) {
+            return Err(ParseVariableError {
+                kind: ParseVariableErrorKind::InvalidVariable,
+            });
+        }
+
+        Ok(self.intern(s))
+    }
+
+    /// Parse a literal such as `A`, `¬A` or `!A`.
+    pub fn parse_literal(&mut self, s: &str) -> Result<Literal, ParseVariableError> {
+        if let Some(v) = s.strip_prefix('¬') {
+            Ok(Literal::new(self.parse_variable(v)?, true))
+        } else if let Some(v) = s.strip_prefix('!') {
+            Ok(Literal::new(self.parse_variable(v)?, true))
+        } else {
+            Ok(Literal::new(self.parse_variable(s)?, false))
+        }
+    }
+
+    /// Parse a clause such as `A ∨ ¬B` or `A \/ !B`.
+    pub fn parse_clause(&mut self, s: &str) -> Result<Clause, ParseVariableError> {
+        let literals = s
+            .split(" ∨ ")
+            .flat_map(|sub| sub.split(" \\/ "))
+            .map(|l| self.parse_literal(l))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Clause::from_literals(literals))
+    }
+
+    /// Display the value with the names of its variables.
+    pub fn display<'a, T>(&'a self, value: &'a T) -> Named<'a, T>
+    where
+        T: DisplayNamed + ?Sized,
+    {
+        Named { names: self, value }
+    }
+}
+
+/// A variable named differently in two tables, see `NameTable::merge`.
+#[derive(Debug)]
+pub struct NameConflict {
+    pub variable: Variable,
+    pub ours: String,
+    pub theirs: String,
+}
+
+impl fmt::Display for NameConflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.ours == self.theirs {
+            write!(
+                f,
+                "variable {} is read from DIMACS in only one table",
+                self.ours
+            )
+        } else {
+            write!(
+                f,
+                "variable {} is named both {} and {}",
+                self.variable, self.ours, self.theirs
+            )
+        }
+    }
+}
+
+impl error::Error for NameConflict {}
+
+/// Values displayed with the names of their variables.
+///
+/// Variables missing from the table are displayed by their index, as in `#3`.
+pub trait DisplayNamed {
+    fn fmt_named(&self, names: &NameTable, f: &mut fmt::Formatter) -> fmt::Result;
+}
+
+/// A value along with the names of its variables, see `NameTable::display`.
+pub struct Named<'a, T: ?Sized> {
+    names: &'a NameTable,
+    value: &'a T,
+}
+
+impl<'a, T> fmt::Display for Named<'a, T>
+where
+    T: DisplayNamed + ?Sized,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.value.fmt_named(self.names, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NameTable;
+
+    #[test]
+    fn test_intern() {
+        let mut names = NameTable::new();
+        let a = names.intern("A");
+        let b = names.intern("B");
+        assert_eq!((a.index(), b.index()), (0, 1));
+        assert_eq!(names.intern("A"), a);
+        assert_eq!(names.len(), 2);
+
+        assert_eq!(names.get("B"), Some(b));
+        assert_eq!(names.get("C"), None);
+        assert_eq!(names.name(b), Some("B"));
+
+        // tables are independent of each other
+        let mut other = NameTable::new();
+        assert_eq!(other.intern("B"), a);
+        assert!(names.parse_variable("x y").is_err());
+    }
+
+    #[test]
+    fn test_dimacs_number() {
+        let mut names = NameTable::new();
+        let v3 = names.parse_variable("v3").unwrap();
+        assert_eq!(names.dimacs_number(v3), None);
+
+        let mut names = NameTable::new();
+        let v3 = names.intern_dimacs(3);
+        assert_eq!(names.dimacs_number(v3), Some(3));
+        assert_eq!(names.name(v3), Some("v3"));
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut names = NameTable::new();
+        names.intern("A");
+        let mut other = names.clone();
+        let b = other.intern("B");
+        names.merge(&other).unwrap();
+        assert_eq!(names.get("B"), Some(b));
+
+        let mut separate = NameTable::new();
+        separate.intern("B");
+        let conflict = names.merge(&separate).unwrap_err();
+        assert_eq!(
+            (conflict.ours.as_str(), conflict.theirs.as_str()),
+            ("A", "B")
+        );
+        assert_eq!(names.len(), 2);
+
+        // a variable of an expression is not the DIMACS variable of the same name
+        let mut dimacs = NameTable::new();
+        dimacs.intern_dimacs(1);
+        let mut expression = NameTable::new();
+        expression.intern("v1");
+        assert!(dimacs.merge(&expression).is_err());
+    }
+
+    #[test]
+    fn test_display() {
+        let mut names = NameTable::new();
+        let clause = names.parse_clause("A ∨ !B").unwrap();
+        assert_eq!(names.display(&clause).to_string(), "A ∨ ¬B");
+        assert_eq!(clause.to_string(), "#0 ∨ ¬#1");
+    }
+}
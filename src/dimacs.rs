@@ -1,7 +1,8 @@
 use std::io::{self, BufRead, BufReader, Read};
 use std::{char, error, fmt, string};
 
-use crate::cnf::{Clause, Literal, CNF};
+use crate::assignment::{Assignment, Truth};
+use crate::cnf::{Clause, Literal, NameTable, Variable, CNF};
 
 #[derive(Debug)]
 pub enum ParseDIMACSError {
@@ -50,13 +51,50 @@ impl fmt::Display for ParseDIMACSError {
 
 impl error::Error for ParseDIMACSError {}
 
+/// The variable standing for DIMACS variable `n` in a CNF made by `parse`.
+pub fn variable(n: u32) -> Variable {
+    Variable::from_index(n as usize - 1)
+}
+
+/// The model as signed DIMACS numbers of variables `1..=num_vars`, as in
+/// the value lines of the competition format.
+///
+/// Variables the model does not assign, such as those declared but not
+/// occurring in any clause, are false.
+pub fn model_literals(names: &NameTable, model: &Assignment, num_vars: usize) -> Vec<i64> {
+    let mut values = vec![false; num_vars];
+    for (var, truth) in model.iter() {
+        match names.dimacs_number(var) {
+            Some(n) if n >= 1 && n as usize <= num_vars => {
+                values[n as usize - 1] = truth == Truth::True;
+            }
+            _ => {}
+        }
+    }
+    values
+        .into_iter()
+        .zip(1..)
+        .map(|(value, n)| if value { n } else { -n })
+        .collect()
+}
+
 pub fn parse<R>(input: R) -> Result<CNF, ParseDIMACSError>
+where
+    R: Read,
+{
+    parse_with_num_vars(input).map(|(cnf, _)| cnf)
+}
+
+/// Parse a CNF along with the number of variables declared in its problem line.
+pub fn parse_with_num_vars<R>(input: R) -> Result<(CNF, usize), ParseDIMACSError>
 where
     R: Read,
 {
     let mut buffer = BufReader::new(input);
     let preamble = parse_preamble(&mut buffer)?;
-    parse_clauses(&mut buffer, preamble)
+    let num_vars = preamble.vars;
+    let cnf = parse_clauses(&mut buffer, preamble)?;
+    Ok((cnf, num_vars))
 }
 
 struct Preamble {
@@ -92,6 +130,10 @@ fn parse_clauses(buffer: impl BufRead, preamble: Preamble) -> Result<CNF, ParseD
         clauses: num_clauses,
     } = preamble;
 
+    let mut names = NameTable::new();
+    let variables: Vec<_> = (1..=num_vars)
+        .map(|n| names.intern_dimacs(n as u32))
+        .collect();
     let mut clauses = Vec::new();
     let mut literals = Vec::new();
 
@@ -116,15 +158,13 @@ fn parse_clauses(buffer: impl BufRead, preamble: Preamble) -> Result<CNF, ParseD
                 continue;
             }
 
-            if token.abs() as usize > num_vars {
+            if token.unsigned_abs() as usize > num_vars {
                 return Err(ParseDIMACSError::UnboundVariable(token));
             }
-            let mut literal: Literal = format!("v{}", token.abs())
-                .parse()
-                .map_err(|_| ParseDIMACSError::MalformedLiteral(token_str.to_owned()))?;
-            if token.is_negative() {
-                literal.negate();
-            }
+            let literal = Literal::new(
+                variables[token.unsigned_abs() as usize - 1],
+                token.is_negative(),
+            );
 
             literals.push(literal);
         }
@@ -134,5 +174,27 @@ fn parse_clauses(buffer: impl BufRead, preamble: Preamble) -> Result<CNF, ParseD
         return Err(ParseDIMACSError::UnexpectedEndOfFile);
     }
 
-    Ok(CNF::from_clauses(clauses))
+    Ok(CNF::with_names(clauses, names))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{model_literals, parse_with_num_vars, variable};
+    use crate::assignment::{Assignment, Truth};
+
+    #[test]
+    fn test_model_literals() {
+        let input = "c comment\np cnf 4 2\n1 -3 0\n-1 3 0\n";
+        let (cnf, num_vars) = parse_with_num_vars(input.as_bytes()).unwrap();
+        assert_eq!(num_vars, 4);
+        assert_eq!(cnf.clauses().count(), 2);
+
+        let mut model = Assignment::new();
+        model.assign(&variable(1), Truth::True);
+        model.assign(&variable(3), Truth::True);
+        assert_eq!(
+            model_literals(cnf.names(), &model, num_vars),
+            vec![1, -2, 3, -4]
+        );
+    }
 }
@@ -1,33 +1,189 @@
+use std::{error, fmt};
+
 use crate::assignment::{Assignment, Truth};
-use crate::cnf::{Clause, Literal, CNF};
+use crate::cnf::{Clause, DisplayNamed, Literal, NameTable, Variable, CNF};
 
+/// Evaluate the CNF under a partial assignment in Kleene's three-valued logic.
 pub fn eval(cnf: &CNF, assignment: &Assignment) -> Truth {
-    for c in cnf.clauses() {
-        match eval_clause(c, assignment) {
+    let mut result = Truth::True;
+    for c in cnf.all_clauses() {
+        match eval_literals(c, assignment) {
             Truth::True => continue,
             Truth::False => return Truth::False,
+            Truth::Undefined => result = Truth::Undefined,
         }
     }
 
-    Truth::True
+    result
+}
+
+/// Clauses satisfied by the assignment.
+pub fn satisfied_clauses<'a>(
+    cnf: &'a CNF,
+    assignment: &'a Assignment,
+) -> impl Iterator<Item = &'a [Literal]> {
+    clauses_with(cnf, assignment, Truth::True)
+}
+
+/// Clauses whose literals are all false under the assignment.
+pub fn falsified_clauses<'a>(
+    cnf: &'a CNF,
+    assignment: &'a Assignment,
+) -> impl Iterator<Item = &'a [Literal]> {
+    clauses_with(cnf, assignment, Truth::False)
+}
+
+/// Clauses that are neither satisfied nor falsified yet.
+pub fn open_clauses<'a>(
+    cnf: &'a CNF,
+    assignment: &'a Assignment,
+) -> impl Iterator<Item = &'a [Literal]> {
+    clauses_with(cnf, assignment, Truth::Undefined)
+}
+
+fn clauses_with<'a>(
+    cnf: &'a CNF,
+    assignment: &'a Assignment,
+    truth: Truth,
+) -> impl Iterator<Item = &'a [Literal]> {
+    cnf.all_clauses()
+        .filter(move |c| eval_literals(*c, assignment) == truth)
+}
+
+/// Reason why an assignment is not a model of a CNF.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyError {
+    Unassigned(Variable),
+    /// The clause at `index` in the original order (starting from 1) is falsified.
+    Falsified {
+        index: usize,
+        clause: Clause,
+    },
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_named(&NameTable::new(), f)
+    }
+}
+
+impl DisplayNamed for VerifyError {
+    fn fmt_named(&self, names: &NameTable, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VerifyError::Unassigned(variable) => {
+                write!(f, "variable {} is unassigned", names.display(variable))
+            }
+            VerifyError::Falsified { index, clause } => write!(
+                f,
+                "clause #{} ({}) is falsified",
+                index,
+                names.display(clause)
+            ),
+        }
+    }
+}
+
+impl error::Error for VerifyError {}
+
+/// Check that the assignment is a total model of the original clauses of the CNF.
+///
+/// Unlike `eval`, unassigned variables are not treated as true.
+pub fn verify(cnf: &CNF, assignment: &Assignment) -> Result<(), VerifyError> {
+    if let Some(l) = cnf
+        .original_clauses()
+        .flat_map(Clause::literals)
+        .find(|l| assignment.get(&l.variable()).is_none())
+    {
+        return Err(VerifyError::Unassigned(l.variable()));
+    }
+
+    for (i, c) in cnf.original_clauses().enumerate() {
+        if eval_clause(c, assignment) == Truth::False {
+            return Err(VerifyError::Falsified {
+                index: i + 1,
+                clause: c.clone(),
+            });
+        }
+    }
+
+    Ok(())
 }
 
 pub fn eval_clause(clause: &Clause, assignment: &Assignment) -> Truth {
-    for l in clause.literals() {
+    eval_literals(clause.literals(), assignment)
+}
+
+/// Evaluate the disjunction of the literals.
+pub fn eval_literals<'a, T>(literals: T, assignment: &Assignment) -> Truth
+where
+    T: IntoIterator<Item = &'a Literal>,
+{
+    let mut result = Truth::False;
+    for l in literals {
         match eval_literal(l, assignment) {
             Truth::True => return Truth::True,
             Truth::False => continue,
+            Truth::Undefined => result = Truth::Undefined,
         }
     }
 
-    Truth::False
+    result
 }
 
+/// Unassigned variables are `Undefined`.
 pub fn eval_literal(literal: &Literal, assignment: &Assignment) -> Truth {
-    let a = assignment.get(literal.variable()).unwrap_or(Truth::True);
+    let a = assignment.value(&literal.variable());
     if literal.is_negated() {
         !a
     } else {
         a
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{eval, falsified_clauses, open_clauses, satisfied_clauses, verify, VerifyError};
+    use crate::assignment::{Assignment, Truth};
+    use crate::cnf::{Literal, CNF};
+
+    #[test]
+    fn test_verify() {
+        let cnf: CNF = "A ∨ B ∧ ¬A ∨ ¬B".parse().unwrap();
+        let (a, b) = (cnf.names().get("A").unwrap(), cnf.names().get("B").unwrap());
+        let mut assignment = Assignment::new();
+        assignment.assign(&a, Truth::False);
+        assert_eq!(eval(&cnf, &assignment), Truth::Undefined);
+        assert_eq!(verify(&cnf, &assignment), Err(VerifyError::Unassigned(b)));
+
+        assignment.assign(&b, Truth::False);
+        assert!(matches!(
+            verify(&cnf, &assignment),
+            Err(VerifyError::Falsified { index: 1, .. })
+        ));
+
+        assignment.assign(&b, Truth::True);
+        assert_eq!(verify(&cnf, &assignment), Ok(()));
+    }
+    #[test]
+    fn test_classify() {
+        let cnf: CNF = "A ∨ B ∧ ¬A ∨ C ∧ ¬B".parse().unwrap();
+        let (a, b) = (cnf.names().get("A").unwrap(), cnf.names().get("B").unwrap());
+        let mut assignment = Assignment::new();
+        assignment.assign(&a, Truth::True);
+        assert_eq!(eval(&cnf, &assignment), Truth::Undefined);
+        assert_eq!(satisfied_clauses(&cnf, &assignment).count(), 1);
+        assert_eq!(open_clauses(&cnf, &assignment).count(), 2);
+        assert_eq!(falsified_clauses(&cnf, &assignment).count(), 0);
+
+        assignment.assign(&b, Truth::True);
+        assert_eq!(eval(&cnf, &assignment), Truth::False);
+        let not_b = Literal::new(b, true);
+        assert_eq!(
+            falsified_clauses(&cnf, &assignment).collect::<Vec<_>>(),
+            vec![&[not_b][..]]
+        );
+
+        assignment.assign(&b, Truth::Undefined);
+        assert_eq!(open_clauses(&cnf, &assignment).count(), 2);
+    }
+}
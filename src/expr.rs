@@ -1,10 +1,8 @@
 use std::{fmt, str};
 
-use crate::cnf::Variable;
-
 #[derive(Clone)]
 pub enum Expr {
-    Var(Variable),
+    Var(String),
     Not(Box<Expr>),
     And(Box<Expr>, Box<Expr>),
     Or(Box<Expr>, Box<Expr>),
@@ -17,7 +15,9 @@ fn imply(e1: Expr, e2: Expr) -> Expr {
 peg::parser! {
   grammar parser() for str {
       rule variable() -> Expr
-          = n:name() {? n.parse().map(Expr::Var).map_err(|_| "not a variable") }
+          = n:name() {?
+              if n.chars().all(char::is_alphanumeric) { Ok(Expr::Var(n.to_owned())) } else { Err("not a variable") }
+          }
 
       rule name() -> &'input str
           = quiet!{ s:$(['a'..='z' | 'A'..='Z'] ['a'..='z' | 'A'..='Z' | '0'..='9' | '_']*) _ { s } }
@@ -67,7 +67,7 @@ impl str::FromStr for Expr {
 impl fmt::Display for Expr {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Expr::Var(v) => v.fmt(f),
+            Expr::Var(v) => f.pad(v),
             Expr::Not(box Expr::Var(v)) => write!(f, "¬{}", v),
             Expr::Not(e) => write!(f, "¬({})", e),
             Expr::And(e1, e2) => write!(f, "({}) ∧ ({})", e1, e2),
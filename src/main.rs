@@ -1,9 +1,17 @@
 use std::fs::File;
-use std::io::{self, Write};
+use std::io::{self, BufWriter, Write};
+use std::iter;
 use std::path::{Path, PathBuf};
+use std::process;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
+use sats::assignment::Assignment;
 use sats::cnf::CNF;
-use sats::solver::Solver;
+use sats::proof::{self, DratWriter, Encoding, Format, LratWriter};
+use sats::solver::dpll::Heuristic;
+use sats::solver::{self, cdcl, Registry, Solver, SolverSpec, Statistics, Status, Terminate};
 use sats::{dimacs, eval, tseytin};
 
 use structopt::StructOpt;
@@ -29,17 +37,224 @@ struct Opt {
     #[structopt(short, long, conflicts_with = "cnf_file")]
     expr: bool,
 
-    #[structopt(short, long, default_value = "CDCL", possible_values = &["CDCL", "DPLL"])]
-    solver: Solver,
+    /// Solver name with optional parameters, such as `cdcl:restarts=luby`
+    #[structopt(short, long, default_value = "CDCL")]
+    solver: SolverSpec,
+
+    /// Branching heuristic of the DPLL solver, short for `dpll:heuristic=...` (--solver dpll only)
+    #[structopt(long, possible_values = &["MOMS", "JW", "JW2", "DLIS", "DLCS", "Bohm"])]
+    heuristic: Option<Heuristic>,
+
+    /// Set solver parameters, given as comma-separated key=value pairs
+    #[structopt(long = "set", number_of_values = 1)]
+    params: Vec<String>,
+
+    /// Give up after the number of seconds, short for `--set timeout=...`
+    #[structopt(long)]
+    timeout: Option<f64>,
+
+    /// Give up after the number of conflicts, short for `--set max-conflicts=...`
+    #[structopt(long)]
+    max_conflicts: Option<u64>,
+
+    /// Print the solver and its configuration before solving
+    #[structopt(long)]
+    print_config: bool,
+
+    /// Write a proof of unsatisfiability to the file (CDCL with --cnf-file only)
+    #[structopt(long, parse(from_os_str))]
+    proof: Option<PathBuf>,
+
+    #[structopt(long, default_value = "DRAT", possible_values = &["DRAT", "LRAT"])]
+    proof_format: Format,
+
+    /// Encoding of DRAT proofs
+    #[structopt(long, default_value = "text", possible_values = &["text", "binary"])]
+    proof_encoding: Encoding,
+
+    /// Check that a model assigns every variable and satisfies every clause
+    #[structopt(long)]
+    verify: bool,
+
+    /// Print statistics of the solver after solving
+    #[structopt(long)]
+    stats: bool,
+
+    /// Print statistics as a table, or as comment lines of the competition format
+    #[structopt(long, default_value = "table", possible_values = &["table", "comments"])]
+    stats_format: StatsFormat,
+
+    /// Print the result in the SAT competition format and exit with 10, 20 or 0
+    /// for SAT, UNSAT or UNKNOWN (--cnf-file only)
+    #[structopt(long)]
+    competition: bool,
+
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(StructOpt, Debug)]
+enum Command {
+    /// Check a proof of unsatisfiability of a DIMACS CNF
+    CheckProof {
+        #[structopt(parse(from_os_str))]
+        cnf_file: PathBuf,
+
+        #[structopt(parse(from_os_str))]
+        proof_file: PathBuf,
+
+        #[structopt(long, default_value = "DRAT", possible_values = &["DRAT", "LRAT"])]
+        format: Format,
+
+        /// Write the trimmed proof in LRAT to the file (DRAT only)
+        #[structopt(long, parse(from_os_str))]
+        lrat: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+enum StatsFormat {
+    Table,
+    Comments,
+}
+
+impl FromStr for StatsFormat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<StatsFormat, Self::Err> {
+        match s {
+            "table" => Ok(StatsFormat::Table),
+            "comments" => Ok(StatsFormat::Comments),
+            _ => Err(format!("unknown statistics format: {}", s)),
+        }
+    }
+}
+
+/// What is printed besides the result.
+#[derive(Debug, Clone, Copy)]
+struct Output {
+    verify: bool,
+    stats: Option<StatsFormat>,
+    competition: bool,
 }
 
-fn run_solve(solver: Solver, cnf: CNF) {
-    if let Some(model) = solver.run(cnf.clone()) {
-        // if let Some(model) = dpll::solve(cnf.clone()) {
-        println!("SAT {}", model);
-        println!("=> {}", eval::eval(&cnf, &model));
+/// Exit code used when `--verify` rejects a model.
+const EXIT_INVALID_MODEL: i32 = 3;
+/// Exit code used when Ctrl-C is pressed at the interactive prompt.
+const EXIT_INTERRUPTED: i32 = 130;
+/// Exit codes of the competition format.
+const EXIT_SATISFIABLE: i32 = 10;
+const EXIT_UNSATISFIABLE: i32 = 20;
+
+/// Maximum length of a value line in the competition format.
+const VALUE_LINE_WIDTH: usize = 78;
+
+fn run_solve(solver: &mut dyn Solver, spec: &SolverSpec, cnf: CNF, output: Output) {
+    let result = solver.run(cnf.clone());
+    print_result(&cnf, result.status(), result.model(), output.verify);
+    if let Some(format) = output.stats {
+        print_statistics(spec, solver.statistics(), format);
+    }
+}
+
+fn print_result(cnf: &CNF, status: Status, model: Option<&Assignment>, verify: bool) {
+    match status {
+        Status::Satisfiable => print_model(cnf, model.unwrap(), verify),
+        Status::Unsatisfiable => println!("UNSAT"),
+        Status::Unknown(interrupt) => println!("UNKNOWN ({})", interrupt),
+    }
+}
+
+/// Print the result of a DIMACS CNF in the format of the SAT competition,
+/// returning the exit code.
+fn print_competition_result(
+    cnf: &CNF,
+    num_vars: usize,
+    status: Status,
+    model: Option<&Assignment>,
+    verify: bool,
+) -> i32 {
+    match status {
+        Status::Satisfiable => {
+            let model = model.unwrap();
+            if verify {
+                verify_model(cnf, model);
+                println!("c model verified");
+            }
+            println!("s SATISFIABLE");
+            print_values(&dimacs::model_literals(cnf.names(), model, num_vars));
+            EXIT_SATISFIABLE
+        }
+        Status::Unsatisfiable => {
+            println!("s UNSATISFIABLE");
+            EXIT_UNSATISFIABLE
+        }
+        Status::Unknown(interrupt) => {
+            println!("c {}", interrupt);
+            println!("s UNKNOWN");
+            0
+        }
+    }
+}
+
+/// Print value lines, the last of which ends with `0`.
+fn print_values(literals: &[i64]) {
+    let mut line = String::from("v");
+    for token in literals
+        .iter()
+        .map(i64::to_string)
+        .chain(iter::once("0".to_owned()))
+    {
+        if line.len() + 1 + token.len() > VALUE_LINE_WIDTH {
+            println!("{}", line);
+            line = String::from("v");
+        }
+        line.push(' ');
+        line.push_str(&token);
+    }
+    println!("{}", line);
+}
+
+fn print_statistics(spec: &SolverSpec, statistics: &Statistics, format: StatsFormat) {
+    for line in statistics_lines(spec, statistics, format) {
+        println!("{}", line);
+    }
+}
+
+/// Lines printed by `--stats`, one per statistic after a heading in a table.
+fn statistics_lines(
+    spec: &SolverSpec,
+    statistics: &Statistics,
+    format: StatsFormat,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+    let prefix = match format {
+        StatsFormat::Table => {
+            lines.push(format!("statistics of {}:", spec));
+            "  "
+        }
+        StatsFormat::Comments => "c ",
+    };
+    for (name, value) in statistics.rows() {
+        lines.push(format!("{}{:<20}{:>14}", prefix, name, value));
+    }
+    lines
+}
+
+fn print_model(cnf: &CNF, model: &Assignment, verify: bool) {
+    println!("SAT {}", cnf.names().display(model));
+    if verify {
+        verify_model(cnf, model);
+        println!("=> verified");
     } else {
-        println!("UNSAT");
+        println!("=> {}", eval::eval(cnf, model));
+    }
+}
+
+/// Exit if the model does not satisfy the CNF.
+fn verify_model(cnf: &CNF, model: &Assignment) {
+    if let Err(e) = eval::verify(cnf, model) {
+        eprintln!("invalid model: {}", cnf.names().display(&e));
+        process::exit(EXIT_INVALID_MODEL);
     }
 }
 
@@ -55,26 +270,153 @@ fn get_cnf(input: &str, is_expr: bool) -> Result<CNF, Box<dyn std::error::Error>
 }
 
 fn solve(
-    solver: Solver,
+    solver: &mut dyn Solver,
+    spec: &SolverSpec,
     is_expr: bool,
     input: impl AsRef<str>,
+    output: Output,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let cnf = get_cnf(input.as_ref(), is_expr)?;
-    run_solve(solver, cnf);
+    run_solve(solver, spec, cnf, output);
     Ok(())
 }
 
+/// Returns the exit code.
 fn solve_file(
-    solver: Solver,
+    solver: &mut dyn Solver,
+    spec: &SolverSpec,
     cnf_file: impl AsRef<Path>,
-) -> Result<(), Box<dyn std::error::Error>> {
+    output: Output,
+) -> Result<i32, Box<dyn std::error::Error>> {
     let file = File::open(cnf_file)?;
-    let cnf = dimacs::parse(file)?;
-    run_solve(solver, cnf);
+    let (cnf, num_vars) = dimacs::parse_with_num_vars(file)?;
+    if !output.competition {
+        run_solve(solver, spec, cnf, output);
+        return Ok(0);
+    }
+
+    let result = solver.run(cnf.clone());
+    if output.stats.is_some() {
+        print_statistics(spec, solver.statistics(), StatsFormat::Comments);
+    }
+    let (status, model) = (result.status(), result.model());
+    Ok(print_competition_result(
+        &cnf,
+        num_vars,
+        status,
+        model,
+        output.verify,
+    ))
+}
+
+fn solve_file_with_proof(
+    spec: &SolverSpec,
+    config: &solver::Config,
+    cnf_file: impl AsRef<Path>,
+    proof_file: impl AsRef<Path>,
+    format: Format,
+    encoding: Encoding,
+    output: Output,
+) -> Result<i32, Box<dyn std::error::Error>> {
+    if format == Format::LRAT && encoding != Encoding::Text {
+        return Err("LRAT proofs are only written as text".into());
+    }
+
+    let file = File::open(cnf_file)?;
+    let (cnf, num_vars) = dimacs::parse_with_num_vars(file)?;
+
+    let proof: Box<dyn Write> = Box::new(BufWriter::new(File::create(proof_file)?));
+    let mut solver = cdcl::Solver::new(config.cdcl.clone());
+    solver.set_limits(config.limits);
+    match format {
+        Format::DRAT => solver.set_proof(DratWriter::new(proof, encoding)),
+        Format::LRAT => solver.set_lrat_proof(LratWriter::new(proof)),
+    }
+    solver.add_cnf(&cnf)?;
+    let status = solver.solve();
+    solver.finish_proof()?;
+
+    if output.competition {
+        if output.stats.is_some() {
+            print_statistics(spec, solver.statistics(), StatsFormat::Comments);
+        }
+        return Ok(print_competition_result(
+            &cnf,
+            num_vars,
+            status,
+            solver.model(),
+            output.verify,
+        ));
+    }
+
+    print_result(&cnf, status, solver.model(), output.verify);
+    if let Some(format) = output.stats {
+        print_statistics(spec, solver.statistics(), format);
+    }
+    Ok(0)
+}
+
+fn check_proof(
+    cnf_file: impl AsRef<Path>,
+    proof_file: impl AsRef<Path>,
+    format: Format,
+    lrat_file: Option<impl AsRef<Path>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cnf = dimacs::parse(File::open(cnf_file)?)?;
+    let proof_file = File::open(proof_file)?;
+
+    match format {
+        Format::DRAT => {
+            let steps = proof::parse_drat(proof_file)?;
+            let trimmed = proof::check_drat(&cnf, &steps)?;
+            if let Some(path) = lrat_file {
+                let mut writer = LratWriter::new(BufWriter::new(File::create(path)?));
+                for step in trimmed {
+                    match step {
+                        proof::LratStep::Add { id, clause, hints } => {
+                            writer.add(id, &clause, &hints)?
+                        }
+                        proof::LratStep::Delete(ids) => writer.delete(&ids)?,
+                    }
+                }
+                writer.flush()?;
+            }
+        }
+        Format::LRAT => {
+            if lrat_file.is_some() {
+                return Err("--lrat is only available for DRAT proofs".into());
+            }
+            let steps = proof::parse_lrat(proof_file)?;
+            proof::check_lrat(&cnf, &steps)?;
+        }
+    }
+
+    println!("VERIFIED");
     Ok(())
 }
 
-fn interactive(solver: Solver, is_expr: bool) -> Result<(), Box<dyn std::error::Error>> {
+fn interactive(
+    solver: &mut dyn Solver,
+    spec: &SolverSpec,
+    is_expr: bool,
+    output: Output,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Ctrl-C cancels the running query, or quits at the prompt
+    let solving = Arc::new(AtomicBool::new(false));
+    let cancel = Arc::new(AtomicBool::new(false));
+    {
+        let solving = Arc::clone(&solving);
+        let cancel = Arc::clone(&cancel);
+        ctrlc::set_handler(move || {
+            if solving.load(Ordering::SeqCst) {
+                cancel.store(true, Ordering::SeqCst);
+            } else {
+                process::exit(EXIT_INTERRUPTED);
+            }
+        })?;
+    }
+    solver.set_terminate(Some(Terminate::flag(Arc::clone(&cancel))));
+
     let mut buf = String::new();
     let mut stdout = io::stdout();
 
@@ -86,13 +428,25 @@ fn interactive(solver: Solver, is_expr: bool) -> Result<(), Box<dyn std::error::
         io::stdin().read_line(&mut buf)?;
 
         let cnf = get_cnf(&buf, is_expr)?;
-        run_solve(solver, cnf);
+        cancel.store(false, Ordering::SeqCst);
+        solving.store(true, Ordering::SeqCst);
+        run_solve(solver, spec, cnf, output);
+        solving.store(false, Ordering::SeqCst);
     }
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let opt = Opt::from_args();
+fn main() {
+    match run(Opt::from_args()) {
+        Ok(code) => process::exit(code),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        }
+    }
+}
 
+/// Returns the exit code.
+fn run(opt: Opt) -> Result<i32, Box<dyn std::error::Error>> {
     let log_level = match opt.verbose {
         0 => log::LevelFilter::Warn,
         1 => log::LevelFilter::Info,
@@ -106,11 +460,104 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .apply()
         .unwrap();
 
+    if let Some(Command::CheckProof {
+        cnf_file,
+        proof_file,
+        format,
+        lrat,
+    }) = opt.command
+    {
+        return check_proof(cnf_file, proof_file, format, lrat).map(|()| 0);
+    }
+
+    if opt.proof.is_some() && opt.cnf_file.is_none() {
+        return Err("--proof requires --cnf-file".into());
+    }
+    if opt.competition && opt.cnf_file.is_none() {
+        return Err("--competition requires --cnf-file".into());
+    }
+
+    let mut spec = opt.solver;
+    if opt.heuristic.is_some() && spec.name() != "dpll" {
+        return Err("--heuristic requires --solver dpll".into());
+    }
+    if let Some(heuristic) = opt.heuristic {
+        spec = spec.with_param("heuristic", &heuristic.to_string());
+    }
+    if let Some(timeout) = opt.timeout {
+        spec = spec.with_param("timeout", &timeout.to_string());
+    }
+    if let Some(max_conflicts) = opt.max_conflicts {
+        spec = spec.with_param("max-conflicts", &max_conflicts.to_string());
+    }
+    for params in &opt.params {
+        spec = spec.with_params(params)?;
+    }
+
+    let output = Output {
+        verify: opt.verify,
+        stats: if opt.stats {
+            Some(opt.stats_format)
+        } else {
+            None
+        },
+        competition: opt.competition,
+    };
+
+    let mut solver = Registry::default().create(&spec)?;
+    if opt.print_config {
+        let config = solver.config().to_pairs(solver.sections());
+        let prefix = if opt.competition { "c " } else { "" };
+        println!("{}config: {}:{}", prefix, spec.name(), config);
+    }
+
+    if let Some(proof) = opt.proof {
+        if spec.name() != "cdcl" {
+            return Err(format!("{} solver cannot emit proofs", spec).into());
+        }
+        let path = opt.cnf_file.unwrap();
+        return solve_file_with_proof(
+            &spec,
+            solver.config(),
+            path,
+            proof,
+            opt.proof_format,
+            opt.proof_encoding,
+            output,
+        );
+    }
+
+    let solver = solver.as_mut();
     match (opt.input, opt.cnf_file, opt.expr) {
         (Some(_), Some(_), _) => unreachable!(),
-        (Some(input), _, is_expr) => solve(opt.solver, is_expr, input),
-        (_, Some(path), false) => solve_file(opt.solver, path),
+        (Some(input), _, is_expr) => solve(solver, &spec, is_expr, input, output).map(|()| 0),
+        (_, Some(path), false) => solve_file(solver, &spec, path, output),
         (_, Some(_), true) => unreachable!(),
-        (None, None, is_expr) => interactive(opt.solver, is_expr),
+        (None, None, is_expr) => interactive(solver, &spec, is_expr, output).map(|()| 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{statistics_lines, StatsFormat};
+    use sats::solver::{SolverSpec, Statistics};
+
+    #[test]
+    fn test_statistics_lines() {
+        let spec = SolverSpec::new("DPLL").with_param("heuristic", "DLIS");
+        let statistics = Statistics {
+            heuristic: Some("DLIS".to_owned()),
+            decisions: 42,
+            ..Statistics::default()
+        };
+
+        let table = statistics_lines(&spec, &statistics, StatsFormat::Table);
+        assert_eq!(table[0], "statistics of dpll:heuristic=DLIS:");
+        assert_eq!(table[1], "  decisions (DLIS)                42");
+        assert_eq!(table.len(), statistics.rows().len() + 1);
+
+        let comments = statistics_lines(&spec, &statistics, StatsFormat::Comments);
+        assert_eq!(comments[0], "c decisions (DLIS)                42");
+        assert!(comments.iter().all(|line| line.starts_with("c ")));
     }
 }
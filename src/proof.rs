@@ -0,0 +1,86 @@
+use std::{error, fmt, str};
+
+mod check;
+mod drat;
+mod lrat;
+mod parse;
+pub use check::{check_drat, check_lrat, CheckError};
+pub use drat::DratWriter;
+pub use lrat::LratWriter;
+pub use parse::{parse_drat, parse_lrat, DratStep, LratStep, ParseProofError};
+
+/// Encoding of clausal proofs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    Text,
+    Binary,
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Encoding::Text => f.pad("text"),
+            Encoding::Binary => f.pad("binary"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseEncodingError(String);
+
+impl fmt::Display for ParseEncodingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown proof encoding: {}", self.0)
+    }
+}
+
+impl error::Error for ParseEncodingError {}
+
+impl str::FromStr for Encoding {
+    type Err = ParseEncodingError;
+    fn from_str(s: &str) -> Result<Encoding, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(Encoding::Text),
+            "binary" => Ok(Encoding::Binary),
+            _ => Err(ParseEncodingError(s.to_owned())),
+        }
+    }
+}
+
+/// Format of clausal proofs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    DRAT,
+    LRAT,
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Format::DRAT => f.pad("DRAT"),
+            Format::LRAT => f.pad("LRAT"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseFormatError(String);
+
+impl fmt::Display for ParseFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown proof format: {}", self.0)
+    }
+}
+
+impl error::Error for ParseFormatError {}
+
+impl str::FromStr for Format {
+    type Err = ParseFormatError;
+    fn from_str(s: &str) -> Result<Format, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "drat" => Ok(Format::DRAT),
+            "lrat" => Ok(Format::LRAT),
+            _ => Err(ParseFormatError(s.to_owned())),
+        }
+    }
+}
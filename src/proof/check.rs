@@ -0,0 +1,709 @@
+use std::collections::{HashMap, HashSet};
+use std::{error, fmt};
+
+use itertools::Itertools;
+use log::warn;
+
+use super::{DratStep, LratStep};
+use crate::cnf::CNF;
+
+#[derive(Debug)]
+pub enum CheckError {
+    /// A variable of the formula, by name, that does not come from a DIMACS file.
+    UnnumberedVariable(String),
+    NotImplied {
+        step: usize,
+        clause: Vec<i64>,
+    },
+    InvalidHint {
+        id: u64,
+        hint: i64,
+    },
+    MissingRatHint {
+        id: u64,
+        candidate: u64,
+    },
+    EmptyRatClause(u64),
+    UnknownClause(u64),
+    DuplicateClause(u64),
+    NoEmptyClause,
+}
+
+impl fmt::Display for CheckError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CheckError::UnnumberedVariable(v) => write!(f, "variable {} has no DIMACS number", v),
+            CheckError::NotImplied { step, clause } => write!(
+                f,
+                "lemma {} 0 at step {} is neither RUP nor RAT",
+                clause.iter().join(" "),
+                step
+            ),
+            CheckError::InvalidHint { id, hint } => write!(
+                f,
+                "hint {} of clause {} is neither unit nor falsified",
+                hint, id
+            ),
+            CheckError::MissingRatHint { id, candidate } => write!(
+                f,
+                "no RAT hint for clause {} in the derivation of clause {}",
+                candidate, id
+            ),
+            CheckError::EmptyRatClause(id) => write!(
+                f,
+                "empty clause {} is not implied by unit propagation and has no pivot",
+                id
+            ),
+            CheckError::UnknownClause(id) => write!(f, "unknown clause: {}", id),
+            CheckError::DuplicateClause(id) => write!(f, "duplicate clause: {}", id),
+            CheckError::NoEmptyClause => write!(f, "the proof does not derive the empty clause"),
+        }
+    }
+}
+
+impl error::Error for CheckError {}
+
+/// Check a DRAT proof of unsatisfiability of the formula by backward checking.
+///
+/// Returns the lemmas needed to derive the empty clause as a trimmed LRAT proof,
+/// where the formula's clauses are numbered from 1 in order.
+pub fn check_drat(cnf: &CNF, proof: &[DratStep]) -> Result<Vec<LratStep>, CheckError> {
+    let formula = numbered_clauses(cnf)?;
+    let num_vars = formula
+        .iter()
+        .chain(proof.iter().map(|step| match step {
+            DratStep::Add(c) | DratStep::Delete(c) => c,
+        }))
+        .flatten()
+        .map(|l| l.unsigned_abs() as usize)
+        .max()
+        .unwrap_or(0);
+
+    let mut db = Database::new(num_vars);
+    let mut index: HashMap<Vec<i64>, Vec<usize>> = HashMap::new();
+    let insert = |db: &mut Database, index: &mut HashMap<_, Vec<_>>, clause: Vec<i64>| {
+        let c = db.add(clause.clone());
+        index.entry(normalized(clause)).or_default().push(c);
+        c
+    };
+
+    let mut target = None;
+    for clause in formula {
+        let c = insert(&mut db, &mut index, clause);
+        if db.clauses[c].is_empty() {
+            target = Some(c);
+        }
+    }
+    let num_original = db.clauses.len();
+
+    // forward pass: replay the proof up to the empty clause
+    let mut events = Vec::new();
+    if target.is_none() {
+        for (step, s) in proof.iter().enumerate() {
+            match s {
+                DratStep::Add(clause) => {
+                    let c = insert(&mut db, &mut index, clause.clone());
+                    events.push((step + 1, Event::Add(c)));
+                    if clause.is_empty() {
+                        target = Some(c);
+                        break;
+                    }
+                }
+                DratStep::Delete(clause) => {
+                    let found = index.get_mut(&normalized(clause.clone())).and_then(|cs| {
+                        let i = cs.iter().position(|&c| db.active[c])?;
+                        Some(cs.swap_remove(i))
+                    });
+                    match found {
+                        Some(c) => {
+                            db.active[c] = false;
+                            events.push((step + 1, Event::Delete(c)));
+                        }
+                        None => warn!(
+                            "ignoring deletion of unknown clause {} 0",
+                            clause.iter().join(" ")
+                        ),
+                    }
+                }
+            }
+        }
+    }
+    let target = match target {
+        Some(c) => c,
+        None => {
+            // the formula may still be refuted by unit propagation
+            let c = db.add(Vec::new());
+            events.push((proof.len() + 1, Event::Add(c)));
+            c
+        }
+    };
+
+    // backward pass: verify the lemmas the empty clause depends on
+    let mut core = vec![false; db.clauses.len()];
+    let mut hints = vec![Vec::new(); db.clauses.len()];
+    core[target] = true;
+    for &(step, event) in events.iter().rev() {
+        match event {
+            Event::Delete(c) => db.active[c] = true,
+            Event::Add(c) => {
+                db.active[c] = false;
+                if !core[c] {
+                    continue;
+                }
+                hints[c] = db
+                    .derive(c, &mut core)
+                    .ok_or_else(|| CheckError::NotImplied {
+                        step,
+                        clause: db.clauses[c].clone(),
+                    })?;
+            }
+        }
+    }
+
+    // number the core lemmas after the original clauses, keeping the deletions of
+    // original clauses so that RAT lemmas are checked against the same formula
+    let mut ids: Vec<u64> = (1..=num_original as u64).collect();
+    ids.resize(db.clauses.len(), 0);
+    let mut next_id = num_original as u64 + 1;
+    let mut lrat = Vec::new();
+    for &(_, event) in &events {
+        match event {
+            Event::Add(c) if core[c] => {
+                ids[c] = next_id;
+                next_id += 1;
+                let hints = hints[c]
+                    .iter()
+                    .map(|h| match h {
+                        Hint::Unit(d) => ids[*d] as i64,
+                        Hint::Candidate(d) => -(ids[*d] as i64),
+                    })
+                    .collect();
+                lrat.push(LratStep::Add {
+                    id: ids[c],
+                    clause: db.clauses[c].clone(),
+                    hints,
+                });
+            }
+            Event::Delete(c) if core[c] || c < num_original => {
+                lrat.push(LratStep::Delete(vec![ids[c]]))
+            }
+            _ => (),
+        }
+    }
+    Ok(lrat)
+}
+
+/// Check an LRAT proof of unsatisfiability of the formula.
+pub fn check_lrat(cnf: &CNF, proof: &[LratStep]) -> Result<(), CheckError> {
+    let mut clauses: HashMap<u64, Vec<i64>> = HashMap::new();
+    for (i, clause) in numbered_clauses(cnf)?.into_iter().enumerate() {
+        clauses.insert(i as u64 + 1, clause);
+    }
+
+    let mut is_refuted = clauses.values().any(Vec::is_empty);
+    for step in proof {
+        match step {
+            LratStep::Add { id, clause, hints } => {
+                if clauses.contains_key(id) {
+                    return Err(CheckError::DuplicateClause(*id));
+                }
+                check_lrat_lemma(&clauses, *id, clause, hints)?;
+                is_refuted |= clause.is_empty();
+                clauses.insert(*id, clause.clone());
+            }
+            LratStep::Delete(ids) => {
+                for id in ids {
+                    clauses.remove(id).ok_or(CheckError::UnknownClause(*id))?;
+                }
+            }
+        }
+    }
+
+    if is_refuted {
+        Ok(())
+    } else {
+        Err(CheckError::NoEmptyClause)
+    }
+}
+
+fn check_lrat_lemma(
+    clauses: &HashMap<u64, Vec<i64>>,
+    id: u64,
+    clause: &[i64],
+    hints: &[i64],
+) -> Result<(), CheckError> {
+    let mut assignment: HashSet<i64> = clause.iter().map(|l| -l).collect();
+    if clause.iter().any(|l| assignment.contains(l)) {
+        return Ok(());
+    }
+
+    let rup_length = hints.iter().position(|h| *h < 0).unwrap_or(hints.len());
+    let (rup_hints, rat_hints) = hints.split_at(rup_length);
+    if propagate_hints(clauses, id, &mut assignment, rup_hints)? {
+        return Ok(());
+    }
+
+    // every clause with the negated pivot must have its resolvent derived
+    let pivot = match clause.first() {
+        Some(&l) => l,
+        None => return Err(CheckError::EmptyRatClause(id)),
+    };
+    let mut groups = HashMap::new();
+    for (i, &h) in rat_hints.iter().enumerate() {
+        if h < 0 {
+            let end = rat_hints[i + 1..]
+                .iter()
+                .position(|h| *h < 0)
+                .map_or(rat_hints.len(), |n| i + 1 + n);
+            groups.insert(h.unsigned_abs(), &rat_hints[i + 1..end]);
+        }
+    }
+    for (&candidate, literals) in clauses.iter().sorted_by_key(|(c, _)| **c) {
+        if !literals.contains(&-pivot) {
+            continue;
+        }
+        let group = groups
+            .get(&candidate)
+            .ok_or(CheckError::MissingRatHint { id, candidate })?;
+        let mut assignment = assignment.clone();
+        let mut is_blocked = false;
+        for &l in literals.iter().filter(|&&l| l != -pivot) {
+            if assignment.contains(&l) {
+                is_blocked = true;
+                break;
+            }
+            assignment.insert(-l);
+        }
+        if !is_blocked && !propagate_hints(clauses, id, &mut assignment, group)? {
+            return Err(CheckError::InvalidHint {
+                id,
+                hint: -(candidate as i64),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Propagate the hint clauses in order, returning whether they end in a conflict.
+fn propagate_hints(
+    clauses: &HashMap<u64, Vec<i64>>,
+    id: u64,
+    assignment: &mut HashSet<i64>,
+    hints: &[i64],
+) -> Result<bool, CheckError> {
+    for &hint in hints {
+        let clause = clauses
+            .get(&(hint as u64))
+            .ok_or(CheckError::UnknownClause(hint as u64))?;
+        if clause.iter().any(|l| assignment.contains(l)) {
+            return Err(CheckError::InvalidHint { id, hint });
+        }
+        let mut open = clause.iter().filter(|l| !assignment.contains(&-**l));
+        match (open.next(), open.next()) {
+            (None, _) => return Ok(true),
+            (Some(&l), None) => {
+                assignment.insert(l);
+            }
+            (Some(_), Some(_)) => return Err(CheckError::InvalidHint { id, hint }),
+        }
+    }
+    Ok(false)
+}
+
+/// Clauses of the formula with DIMACS literals, in their original order.
+fn numbered_clauses(cnf: &CNF) -> Result<Vec<Vec<i64>>, CheckError> {
+    cnf.original_clauses()
+        .map(|clause| {
+            clause
+                .literals()
+                .map(|l| match cnf.names().dimacs_number(l.variable()) {
+                    Some(n) if l.is_negated() => Ok(-i64::from(n)),
+                    Some(n) => Ok(i64::from(n)),
+                    None => Err(CheckError::UnnumberedVariable(
+                        cnf.names().display(&l.variable()).to_string(),
+                    )),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn normalized(mut clause: Vec<i64>) -> Vec<i64> {
+    clause.sort_unstable();
+    clause.dedup();
+    clause
+}
+
+#[derive(Clone, Copy)]
+enum Event {
+    Add(usize),
+    Delete(usize),
+}
+
+#[derive(Clone, Copy)]
+enum Hint {
+    Unit(usize),
+    Candidate(usize),
+}
+
+enum Status {
+    Satisfied,
+    Falsified,
+    Unit(i64),
+    Open,
+}
+
+/// Clauses with two watched literals, and a scratch assignment for unit propagation.
+///
+/// `literals` holds each clause without duplicates, its first two literals being watched.
+/// Watches are kept for inactive clauses too; they are valid again once the assignment is undone.
+struct Database {
+    clauses: Vec<Vec<i64>>,
+    literals: Vec<Vec<i64>>,
+    active: Vec<bool>,
+    occurrences: Vec<Vec<usize>>,
+    watches: Vec<Vec<usize>>,
+    units: Vec<usize>,
+    values: Vec<Option<bool>>,
+    reasons: Vec<Option<usize>>,
+    trail: Vec<i64>,
+}
+
+impl Database {
+    fn new(num_vars: usize) -> Self {
+        Database {
+            clauses: Vec::new(),
+            literals: Vec::new(),
+            active: Vec::new(),
+            occurrences: vec![Vec::new(); 2 * num_vars + 2],
+            watches: vec![Vec::new(); 2 * num_vars + 2],
+            units: Vec::new(),
+            values: vec![None; num_vars + 1],
+            reasons: vec![None; num_vars + 1],
+            trail: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, clause: Vec<i64>) -> usize {
+        let c = self.clauses.len();
+        let literals = normalized(clause.clone());
+        for &l in &literals {
+            self.occurrences[code(l)].push(c);
+        }
+        if literals.len() <= 1 {
+            self.units.push(c);
+        } else {
+            self.watches[code(literals[0])].push(c);
+            self.watches[code(literals[1])].push(c);
+        }
+        self.clauses.push(clause);
+        self.literals.push(literals);
+        self.active.push(true);
+        c
+    }
+
+    /// Derive the clause by RUP, or by RAT on its first literal, marking the antecedents as core.
+    fn derive(&mut self, c: usize, core: &mut [bool]) -> Option<Vec<Hint>> {
+        let clause = self.clauses[c].clone();
+        if let Some(chain) = self.rup(&clause) {
+            for &d in &chain {
+                core[d] = true;
+            }
+            return Some(chain.into_iter().map(Hint::Unit).collect());
+        }
+
+        let pivot = *clause.first()?;
+        let candidates: Vec<_> = self.occurrences[code(-pivot)]
+            .iter()
+            .copied()
+            .filter(|&d| self.active[d])
+            .collect();
+        let mut hints = Vec::new();
+        for d in candidates {
+            let mut resolvent = clause.clone();
+            resolvent.extend(self.clauses[d].iter().filter(|&&l| l != -pivot));
+            let chain = self.rup(&resolvent)?;
+            core[d] = true;
+            hints.push(Hint::Candidate(d));
+            for e in chain {
+                core[e] = true;
+                hints.push(Hint::Unit(e));
+            }
+        }
+        Some(hints)
+    }
+
+    /// Check that unit propagation refutes the negation of the clause.
+    ///
+    /// Returns the reason clauses in propagation order, ending with the conflicting clause.
+    fn rup(&mut self, clause: &[i64]) -> Option<Vec<usize>> {
+        let result = self.propagate(clause);
+        for l in self.trail.drain(..) {
+            self.values[l.unsigned_abs() as usize] = None;
+            self.reasons[l.unsigned_abs() as usize] = None;
+        }
+        result
+    }
+
+    fn propagate(&mut self, clause: &[i64]) -> Option<Vec<usize>> {
+        for &l in clause {
+            match self.value(l) {
+                // tautology
+                Some(true) => return Some(Vec::new()),
+                Some(false) => continue,
+                None => self.assign(-l, None),
+            }
+        }
+
+        for i in 0..self.units.len() {
+            let c = self.units[i];
+            if self.active[c] && self.visit(c) {
+                return Some(self.antecedents(c));
+            }
+        }
+
+        let mut head = 0;
+        while head < self.trail.len() {
+            let falsified = -self.trail[head];
+            head += 1;
+            if let Some(c) = self.propagate_watches(falsified) {
+                return Some(self.antecedents(c));
+            }
+        }
+        None
+    }
+
+    /// Visit the clauses watching the falsified literal, returning a falsified clause if any.
+    fn propagate_watches(&mut self, falsified: i64) -> Option<usize> {
+        let mut watchers = std::mem::take(&mut self.watches[code(falsified)]);
+        let mut conflict = None;
+        let mut i = 0;
+        while i < watchers.len() {
+            let c = watchers[i];
+            if !self.active[c] {
+                i += 1;
+                continue;
+            }
+
+            if self.literals[c][0] == falsified {
+                self.literals[c].swap(0, 1);
+            }
+            let other = self.literals[c][0];
+            if self.value(other) == Some(true) {
+                i += 1;
+                continue;
+            }
+
+            let replacement = (2..self.literals[c].len())
+                .find(|&k| self.value(self.literals[c][k]) != Some(false));
+            if let Some(k) = replacement {
+                self.literals[c].swap(1, k);
+                self.watches[code(self.literals[c][1])].push(c);
+                watchers.swap_remove(i);
+                continue;
+            }
+
+            if self.value(other) == Some(false) {
+                conflict = Some(c);
+                break;
+            }
+            self.assign(other, Some(c));
+            i += 1;
+        }
+        self.watches[code(falsified)] = watchers;
+        conflict
+    }
+
+    /// Assign the implied literal of the clause, returning whether it is falsified.
+    fn visit(&mut self, c: usize) -> bool {
+        match self.status(c) {
+            Status::Falsified => true,
+            Status::Unit(l) => {
+                self.assign(l, Some(c));
+                false
+            }
+            Status::Satisfied | Status::Open => false,
+        }
+    }
+
+    fn status(&self, c: usize) -> Status {
+        let mut unit = None;
+        for &l in &self.literals[c] {
+            match self.value(l) {
+                Some(true) => return Status::Satisfied,
+                Some(false) => continue,
+                None if unit.is_none() || unit == Some(l) => unit = Some(l),
+                None => return Status::Open,
+            }
+        }
+        match unit {
+            Some(l) => Status::Unit(l),
+            None => Status::Falsified,
+        }
+    }
+
+    fn antecedents(&self, conflict: usize) -> Vec<usize> {
+        let mut needed: HashSet<_> = self.clauses[conflict].iter().map(|l| l.abs()).collect();
+        let mut chain = vec![conflict];
+        for l in self.trail.iter().rev() {
+            if !needed.contains(&l.abs()) {
+                continue;
+            }
+            if let Some(r) = self.reasons[l.unsigned_abs() as usize] {
+                chain.push(r);
+                needed.extend(self.clauses[r].iter().map(|l| l.abs()));
+            }
+        }
+        chain.reverse();
+        chain
+    }
+
+    fn value(&self, l: i64) -> Option<bool> {
+        self.values[l.unsigned_abs() as usize].map(|v| v == (l > 0))
+    }
+
+    fn assign(&mut self, l: i64, reason: Option<usize>) {
+        self.values[l.unsigned_abs() as usize] = Some(l > 0);
+        self.reasons[l.unsigned_abs() as usize] = reason;
+        self.trail.push(l);
+    }
+}
+
+fn code(l: i64) -> usize {
+    2 * l.unsigned_abs() as usize + (l < 0) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_drat, check_lrat, CheckError};
+    use crate::cnf::CNF;
+    use crate::dimacs;
+    use crate::proof::{DratStep, LratStep};
+
+    #[test]
+    fn test_drat() {
+        let input = "p cnf 2 4\n1 2 0\n-1 2 0\n1 -2 0\n-1 -2 0\n";
+        let cnf = dimacs::parse(input.as_bytes()).unwrap();
+        let proof = vec![
+            DratStep::Add(vec![1]),
+            DratStep::Delete(vec![2, 1]),
+            DratStep::Add(vec![]),
+        ];
+        let lrat = check_drat(&cnf, &proof).unwrap();
+        assert_eq!(lrat.len(), 3);
+        assert!(check_lrat(&cnf, &lrat).is_ok());
+
+        assert!(matches!(
+            check_drat(&cnf, &[]),
+            Err(CheckError::NotImplied { .. })
+        ));
+
+        // variables of expressions have no DIMACS number, whatever their name
+        let cnf: CNF = "v1 ∧ ¬v1".parse().unwrap();
+        assert!(matches!(
+            check_drat(&cnf, &[]),
+            Err(CheckError::UnnumberedVariable(_))
+        ));
+    }
+
+    #[test]
+    fn test_drat_rat() {
+        // `3` is RAT once the original clause `-3 5` is deleted
+        let input = "p cnf 5 6\n1 2 0\n-1 2 0\n1 -2 0\n-1 -2 0\n-3 1 0\n-3 5 0\n";
+        let cnf = dimacs::parse(input.as_bytes()).unwrap();
+        let proof = vec![
+            DratStep::Delete(vec![-3, 5]),
+            DratStep::Add(vec![3]),
+            DratStep::Add(vec![]),
+        ];
+        let lrat = check_drat(&cnf, &proof).unwrap();
+        assert_eq!(lrat[0], LratStep::Delete(vec![6]));
+        assert!(check_lrat(&cnf, &lrat).is_ok());
+    }
+
+    #[test]
+    fn test_lrat() {
+        let input = "p cnf 2 4\n1 2 0\n-1 2 0\n1 -2 0\n-1 -2 0\n";
+        let cnf = dimacs::parse(input.as_bytes()).unwrap();
+        let unit = LratStep::Add {
+            id: 5,
+            clause: vec![1],
+            hints: vec![1, 3],
+        };
+        let empty = LratStep::Add {
+            id: 6,
+            clause: vec![],
+            hints: vec![5, 2, 4],
+        };
+        assert!(check_lrat(&cnf, &[unit.clone(), empty]).is_ok());
+        assert!(check_lrat(&cnf, std::slice::from_ref(&unit)).is_err());
+
+        let wrong = LratStep::Add {
+            id: 6,
+            clause: vec![],
+            hints: vec![5, 4],
+        };
+        assert!(check_lrat(&cnf, &[unit.clone(), wrong]).is_err());
+
+        // RAT on -3, which no clause contains, then on 3 with the candidate clause 5
+        let definition = LratStep::Add {
+            id: 5,
+            clause: vec![-3, 1],
+            hints: vec![],
+        };
+        let rat = LratStep::Add {
+            id: 6,
+            clause: vec![3, 2],
+            hints: vec![-5, 1],
+        };
+        let unit = LratStep::Add {
+            id: 7,
+            clause: vec![1],
+            hints: vec![1, 3],
+        };
+        let empty = LratStep::Add {
+            id: 8,
+            clause: vec![],
+            hints: vec![7, 2, 4],
+        };
+        assert!(check_lrat(&cnf, &[definition.clone(), rat, unit, empty]).is_ok());
+        let missing = LratStep::Add {
+            id: 6,
+            clause: vec![3, 2],
+            hints: vec![],
+        };
+        assert!(matches!(
+            check_lrat(&cnf, &[definition, missing]),
+            Err(CheckError::MissingRatHint { .. })
+        ));
+    }
+
+    #[test]
+    fn test_lrat_repeated_literals() {
+        let cnf = dimacs::parse("p cnf 2 1\n1 2 0\n".as_bytes()).unwrap();
+        let lemma = |id, clause| LratStep::Add {
+            id,
+            clause,
+            hints: vec![],
+        };
+        let empty = LratStep::Add {
+            id: 4,
+            clause: vec![],
+            hints: vec![2, 3],
+        };
+        assert!(matches!(
+            check_lrat(&cnf, &[lemma(2, vec![1, 1]), lemma(3, vec![-1, -1]), empty]),
+            Err(CheckError::MissingRatHint { id: 3, .. })
+        ));
+
+        let rat_empty = LratStep::Add {
+            id: 2,
+            clause: vec![],
+            hints: vec![-1],
+        };
+        assert!(matches!(
+            check_lrat(&cnf, &[rat_empty]),
+            Err(CheckError::EmptyRatClause(2))
+        ));
+    }
+}
@@ -0,0 +1,89 @@
+use std::io::{self, Write};
+
+use super::Encoding;
+
+/// Writes clause additions and deletions of a DRAT proof.
+///
+/// Literals are given as DIMACS integers.
+pub struct DratWriter<W> {
+    inner: W,
+    encoding: Encoding,
+}
+
+impl<W: Write> DratWriter<W> {
+    pub fn new(inner: W, encoding: Encoding) -> Self {
+        DratWriter { inner, encoding }
+    }
+
+    pub fn add(&mut self, clause: &[i64]) -> io::Result<()> {
+        self.write_line(None, clause)
+    }
+
+    pub fn delete(&mut self, clause: &[i64]) -> io::Result<()> {
+        self.write_line(Some(b'd'), clause)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    fn write_line(&mut self, tag: Option<u8>, clause: &[i64]) -> io::Result<()> {
+        match self.encoding {
+            Encoding::Text => {
+                if let Some(tag) = tag {
+                    write!(self.inner, "{} ", tag as char)?;
+                }
+                for l in clause {
+                    write!(self.inner, "{} ", l)?;
+                }
+                writeln!(self.inner, "0")
+            }
+            Encoding::Binary => {
+                self.inner.write_all(&[tag.unwrap_or(b'a')])?;
+                for &l in clause {
+                    write_binary_literal(&mut self.inner, l)?;
+                }
+                self.inner.write_all(&[0])
+            }
+        }
+    }
+}
+
+/// Write a literal as `2 * variable + sign` in 7-bit variable-length encoding.
+fn write_binary_literal(w: &mut impl Write, literal: i64) -> io::Result<()> {
+    let mut x = 2 * literal.unsigned_abs() + (literal < 0) as u64;
+    while x > 0x7f {
+        w.write_all(&[(x & 0x7f) as u8 | 0x80])?;
+        x >>= 7;
+    }
+    w.write_all(&[x as u8])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DratWriter, Encoding};
+
+    #[test]
+    fn test_text() {
+        let mut w = DratWriter::new(Vec::new(), Encoding::Text);
+        w.add(&[1, -2]).unwrap();
+        w.delete(&[3]).unwrap();
+        w.add(&[]).unwrap();
+        assert_eq!(w.into_inner(), b"1 -2 0\nd 3 0\n0\n");
+    }
+
+    #[test]
+    fn test_binary() {
+        let mut w = DratWriter::new(Vec::new(), Encoding::Binary);
+        w.add(&[1, -2, 64]).unwrap();
+        w.delete(&[-63]).unwrap();
+        assert_eq!(
+            w.into_inner(),
+            vec![b'a', 2, 5, 0x80, 0x01, 0, b'd', 0x7f, 0]
+        );
+    }
+}
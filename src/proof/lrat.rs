@@ -0,0 +1,44 @@
+use std::io::{self, Write};
+
+/// Writes clause additions and deletions of a textual LRAT proof.
+///
+/// Literals are given as DIMACS integers, hints as clause IDs.
+pub struct LratWriter<W> {
+    inner: W,
+    last_id: u64,
+}
+
+impl<W: Write> LratWriter<W> {
+    pub fn new(inner: W) -> Self {
+        LratWriter { inner, last_id: 0 }
+    }
+
+    pub fn add(&mut self, id: u64, clause: &[i64], hints: &[i64]) -> io::Result<()> {
+        self.last_id = id;
+        write!(self.inner, "{} ", id)?;
+        for l in clause {
+            write!(self.inner, "{} ", l)?;
+        }
+        write!(self.inner, "0 ")?;
+        for h in hints {
+            write!(self.inner, "{} ", h)?;
+        }
+        writeln!(self.inner, "0")
+    }
+
+    pub fn delete(&mut self, ids: &[u64]) -> io::Result<()> {
+        write!(self.inner, "{} d ", self.last_id)?;
+        for id in ids {
+            write!(self.inner, "{} ", id)?;
+        }
+        writeln!(self.inner, "0")
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
@@ -0,0 +1,249 @@
+use std::io::{self, Read};
+use std::{error, fmt};
+
+/// A step of a DRAT proof.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DratStep {
+    Add(Vec<i64>),
+    Delete(Vec<i64>),
+}
+
+/// A step of an LRAT proof.
+///
+/// Hints are clause IDs; a negative hint starts the RAT candidate clause with that ID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LratStep {
+    Add {
+        id: u64,
+        clause: Vec<i64>,
+        hints: Vec<i64>,
+    },
+    Delete(Vec<u64>),
+}
+
+#[derive(Debug)]
+pub enum ParseProofError {
+    MalformedLiteral(String),
+    MalformedClauseID(String),
+    MalformedStep(String),
+    UnexpectedEndOfFile,
+    IO(io::Error),
+}
+
+impl From<io::Error> for ParseProofError {
+    fn from(err: io::Error) -> ParseProofError {
+        ParseProofError::IO(err)
+    }
+}
+
+impl fmt::Display for ParseProofError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseProofError::MalformedLiteral(literal) => {
+                write!(f, "malformed literal: {}", literal)
+            }
+            ParseProofError::MalformedClauseID(id) => write!(f, "malformed clause ID: {}", id),
+            ParseProofError::MalformedStep(tag) => write!(f, "malformed proof step: {}", tag),
+            ParseProofError::UnexpectedEndOfFile => write!(f, "unexpected end of file"),
+            ParseProofError::IO(e) => write!(f, "IO error: {}", e),
+        }
+    }
+}
+
+impl error::Error for ParseProofError {}
+
+/// Parse a DRAT proof, detecting the binary encoding.
+pub fn parse_drat<R>(mut input: R) -> Result<Vec<DratStep>, ParseProofError>
+where
+    R: Read,
+{
+    let mut bytes = Vec::new();
+    input.read_to_end(&mut bytes)?;
+    if is_binary(&bytes) {
+        parse_binary_drat(&bytes)
+    } else {
+        parse_text_drat(&String::from_utf8_lossy(&bytes))
+    }
+}
+
+/// Parse a textual LRAT proof.
+pub fn parse_lrat<R>(mut input: R) -> Result<Vec<LratStep>, ParseProofError>
+where
+    R: Read,
+{
+    let mut text = String::new();
+    input.read_to_string(&mut text)?;
+
+    let mut steps = Vec::new();
+    let mut tokens = tokens(&text).peekable();
+    while let Some(id) = tokens.next() {
+        let id = parse_clause_id(id)?;
+        if tokens.peek() == Some(&"d") {
+            tokens.next();
+            let ids = until_zero(&mut tokens, parse_clause_id)?;
+            steps.push(LratStep::Delete(ids));
+        } else {
+            let clause = until_zero(&mut tokens, parse_literal)?;
+            let hints = until_zero(&mut tokens, parse_literal)?;
+            steps.push(LratStep::Add { id, clause, hints });
+        }
+    }
+    Ok(steps)
+}
+
+/// Binary proofs start with an `a` tag or contain non-printable bytes early on.
+fn is_binary(bytes: &[u8]) -> bool {
+    bytes
+        .iter()
+        .take(16)
+        .any(|&b| b == 0 || b >= 0x80 || (b < 0x20 && !(b as char).is_ascii_whitespace()))
+        || bytes.first() == Some(&b'a')
+}
+
+fn parse_text_drat(text: &str) -> Result<Vec<DratStep>, ParseProofError> {
+    let mut steps = Vec::new();
+    let mut tokens = tokens(text).peekable();
+    while let Some(&token) = tokens.peek() {
+        if token == "d" {
+            tokens.next();
+            steps.push(DratStep::Delete(until_zero(&mut tokens, parse_literal)?));
+        } else {
+            steps.push(DratStep::Add(until_zero(&mut tokens, parse_literal)?));
+        }
+    }
+    Ok(steps)
+}
+
+fn parse_binary_drat(bytes: &[u8]) -> Result<Vec<DratStep>, ParseProofError> {
+    let mut steps = Vec::new();
+    let mut bytes = bytes.iter().copied();
+    while let Some(tag) = bytes.next() {
+        let mut clause = Vec::new();
+        loop {
+            let mut x: u64 = 0;
+            let mut shift = 0;
+            loop {
+                let b = bytes.next().ok_or(ParseProofError::UnexpectedEndOfFile)?;
+                if shift >= 64 || (shift == 63 && b & 0x7f > 1) {
+                    return Err(ParseProofError::MalformedLiteral(
+                        "binary literal longer than 64 bits".to_owned(),
+                    ));
+                }
+                x |= u64::from(b & 0x7f) << shift;
+                shift += 7;
+                if b & 0x80 == 0 {
+                    break;
+                }
+            }
+            if x == 0 {
+                break;
+            }
+            let variable = (x >> 1) as i64;
+            clause.push(if x & 1 == 1 { -variable } else { variable });
+        }
+        match tag {
+            b'a' => steps.push(DratStep::Add(clause)),
+            b'd' => steps.push(DratStep::Delete(clause)),
+            _ => return Err(ParseProofError::MalformedStep(format!("{:#x}", tag))),
+        }
+    }
+    Ok(steps)
+}
+
+/// Whitespace-separated tokens outside of comment lines.
+fn tokens(text: &str) -> impl Iterator<Item = &str> {
+    text.lines()
+        .filter(|line| !line.starts_with('c'))
+        .flat_map(|line| line.split_whitespace())
+}
+
+fn until_zero<'a, T, F>(
+    tokens: &mut impl Iterator<Item = &'a str>,
+    parse: F,
+) -> Result<Vec<T>, ParseProofError>
+where
+    T: Default + PartialEq,
+    F: Fn(&str) -> Result<T, ParseProofError>,
+{
+    let mut items = Vec::new();
+    loop {
+        let token = tokens.next().ok_or(ParseProofError::UnexpectedEndOfFile)?;
+        let item = parse(token)?;
+        if item == T::default() {
+            return Ok(items);
+        }
+        items.push(item);
+    }
+}
+
+fn parse_literal(token: &str) -> Result<i64, ParseProofError> {
+    token
+        .parse()
+        .map_err(|_| ParseProofError::MalformedLiteral(token.to_owned()))
+}
+
+fn parse_clause_id(token: &str) -> Result<u64, ParseProofError> {
+    token
+        .parse()
+        .map_err(|_| ParseProofError::MalformedClauseID(token.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_drat, parse_lrat, DratStep, LratStep, ParseProofError};
+
+    #[test]
+    fn test_drat() {
+        let text = "c comment\n1 -2 0\nd 1 -2 0\n0\n";
+        let binary = [b'a', 2, 5, 0, b'd', 2, 5, 0, b'a', 0];
+        let expected = vec![
+            DratStep::Add(vec![1, -2]),
+            DratStep::Delete(vec![1, -2]),
+            DratStep::Add(vec![]),
+        ];
+        assert_eq!(parse_drat(text.as_bytes()).unwrap(), expected);
+        assert_eq!(parse_drat(&binary[..]).unwrap(), expected);
+
+        let mut overlong = vec![b'a'];
+        overlong.extend(&[0xff; 10]);
+        overlong.extend(&[1, 0]);
+        assert!(matches!(
+            parse_drat(&overlong[..]),
+            Err(ParseProofError::MalformedLiteral(_))
+        ));
+
+        // the tenth byte holds only the highest bit
+        let mut overflowing = vec![b'a'];
+        overflowing.extend(&[0xff; 9]);
+        overflowing.extend(&[2, 0]);
+        assert!(matches!(
+            parse_drat(&overflowing[..]),
+            Err(ParseProofError::MalformedLiteral(_))
+        ));
+        let largest = overflowing.len() - 2;
+        overflowing[largest] = 1;
+        assert_eq!(
+            parse_drat(&overflowing[..]).unwrap(),
+            vec![DratStep::Add(vec![-i64::MAX])]
+        );
+    }
+
+    #[test]
+    fn test_lrat() {
+        let text = "5 1 0 1 -2 3 0\n5 d 1 2 0\n6 0 5 4 0\n";
+        let expected = vec![
+            LratStep::Add {
+                id: 5,
+                clause: vec![1],
+                hints: vec![1, -2, 3],
+            },
+            LratStep::Delete(vec![1, 2]),
+            LratStep::Add {
+                id: 6,
+                clause: vec![],
+                hints: vec![5, 4],
+            },
+        ];
+        assert_eq!(parse_lrat(text.as_bytes()).unwrap(), expected);
+    }
+}
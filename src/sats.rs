@@ -8,5 +8,6 @@ pub mod cnf;
 pub mod dimacs;
 pub mod eval;
 pub mod expr;
+pub mod proof;
 pub mod solver;
 pub mod tseytin;
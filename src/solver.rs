@@ -1,61 +1,85 @@
-use std::{error, fmt, str};
-
 use crate::assignment::Assignment;
 use crate::cnf::CNF;
 
+mod budget;
 pub mod cdcl;
+mod config;
 pub mod dpll;
+mod registry;
+mod statistics;
+mod watch;
+pub use budget::{Interrupt, Terminate};
+pub use config::{Config, ConfigBuilder, ConfigureError, Limits, Section};
+pub use registry::{ParseSolverError, Registry, RegistryError, SolverSpec};
+pub use statistics::{Statistics, Times};
 
-#[derive(PartialEq, Eq, Clone, Copy, Hash, Debug)]
-pub enum Solver {
-    DPLL,
-    CDCL,
+/// Outcome of a solver run, without the model.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    Satisfiable,
+    Unsatisfiable,
+    Unknown(Interrupt),
 }
 
-impl Solver {
-    pub fn all() -> impl Iterator<Item = Solver> {
-        vec![Solver::DPLL, Solver::CDCL].into_iter()
-    }
+/// Outcome of a solver run.
+#[derive(Clone)]
+pub enum SolveResult {
+    Satisfiable(Assignment),
+    Unsatisfiable,
+    Unknown(Interrupt),
+}
 
-    pub fn run(&self, cnf: CNF) -> Option<Assignment> {
+impl SolveResult {
+    pub fn status(&self) -> Status {
         match self {
-            Solver::DPLL => dpll::solve(cnf),
-            Solver::CDCL => cdcl::solve(cnf),
+            SolveResult::Satisfiable(_) => Status::Satisfiable,
+            SolveResult::Unsatisfiable => Status::Unsatisfiable,
+            SolveResult::Unknown(interrupt) => Status::Unknown(*interrupt),
         }
     }
-}
 
-impl fmt::Display for Solver {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    pub fn model(&self) -> Option<&Assignment> {
         match self {
-            Solver::DPLL => f.pad("DPLL"),
-            Solver::CDCL => f.pad("CDCL"),
+            SolveResult::Satisfiable(model) => Some(model),
+            _ => None,
         }
     }
-}
-
-#[derive(Debug)]
-pub enum ParseSolverError {
-    UnknownSolver(String),
-}
 
-impl fmt::Display for ParseSolverError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    pub fn into_model(self) -> Option<Assignment> {
         match self {
-            ParseSolverError::UnknownSolver(s) => write!(f, "unknown solver: {}", s),
+            SolveResult::Satisfiable(model) => Some(model),
+            _ => None,
         }
     }
 }
 
-impl error::Error for ParseSolverError {}
+/// A SAT solving engine, created through a `Registry`.
+pub trait Solver {
+    /// Solve the CNF from scratch within the limits of the configuration.
+    fn run(&mut self, cnf: CNF) -> SolveResult;
+
+    /// Statistics of the last call to `run` alone, not accumulated over calls.
+    fn statistics(&self) -> &Statistics;
+
+    /// Poll `terminate` during each following call to `run`, which returns
+    /// `Unknown(Interrupt::Cancelled)` once it is requested.
+    fn set_terminate(&mut self, terminate: Option<Terminate>);
+
+    fn config(&self) -> &Config;
+
+    fn config_mut(&mut self) -> &mut Config;
+
+    /// Sections of the configuration the engine reads.
+    fn sections(&self) -> &[Section];
 
-impl str::FromStr for Solver {
-    type Err = ParseSolverError;
-    fn from_str(s: &str) -> Result<Solver, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "cdcl" => Ok(Solver::CDCL),
-            "dpll" => Ok(Solver::DPLL),
-            _ => Err(ParseSolverError::UnknownSolver(s.to_owned())),
+    /// Set a parameter of the engine from its textual form, as `Config::set` does.
+    ///
+    /// Fails on parameters that do not apply to the engine.
+    fn configure(&mut self, key: &str, value: &str) -> Result<(), ConfigureError> {
+        if self.sections().contains(&Section::of(key)?) {
+            self.config_mut().set(key, value)
+        } else {
+            Err(ConfigureError::UnsupportedKey(key.to_owned()))
         }
     }
 }
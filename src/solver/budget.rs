@@ -0,0 +1,127 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use super::Limits;
+
+/// Why a run stopped before deciding satisfiability.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Interrupt {
+    Conflicts,
+    Propagations,
+    Decisions,
+    Timeout,
+    Cancelled,
+}
+
+impl fmt::Display for Interrupt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Interrupt::Conflicts => f.pad("conflict limit reached"),
+            Interrupt::Propagations => f.pad("propagation limit reached"),
+            Interrupt::Decisions => f.pad("decision limit reached"),
+            Interrupt::Timeout => f.pad("timeout"),
+            Interrupt::Cancelled => f.pad("cancelled"),
+        }
+    }
+}
+
+/// A request to stop a run, polled by the solver between steps of the search.
+///
+/// It can be cloned and shared, so that another thread or a signal handler
+/// stops the run by setting the flag it was created from.
+#[derive(Clone)]
+pub struct Terminate(Arc<dyn Fn() -> bool + Send + Sync>);
+
+impl Terminate {
+    /// Stop once `flag` is set.
+    pub fn flag(flag: Arc<AtomicBool>) -> Terminate {
+        Terminate::callback(move || flag.load(Ordering::Relaxed))
+    }
+
+    /// Stop once `callback` returns `true`.
+    pub fn callback<F>(callback: F) -> Terminate
+    where
+        F: Fn() -> bool + Send + Sync + 'static,
+    {
+        Terminate(Arc::new(callback))
+    }
+
+    pub fn is_requested(&self) -> bool {
+        (self.0)()
+    }
+}
+
+impl From<Arc<AtomicBool>> for Terminate {
+    fn from(flag: Arc<AtomicBool>) -> Terminate {
+        Terminate::flag(flag)
+    }
+}
+
+impl fmt::Debug for Terminate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("Terminate")
+    }
+}
+
+/// Counts of a solver that are subject to limits.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Counts {
+    pub conflicts: u64,
+    pub propagations: u64,
+    pub decisions: u64,
+}
+
+/// `Limits` of a single run, counted from when the run started, and the
+/// request to stop it.
+#[derive(Debug)]
+pub struct Budget {
+    limits: Limits,
+    terminate: Option<Terminate>,
+    start: Counts,
+    deadline: Option<Instant>,
+}
+
+impl Budget {
+    pub fn start(limits: Limits, terminate: Option<Terminate>, counts: Counts) -> Budget {
+        Budget {
+            limits,
+            terminate,
+            start: counts,
+            deadline: limits.time.map(|t| Instant::now() + t),
+        }
+    }
+
+    /// The limit reached with the current counts, if any.
+    pub fn check(&self, counts: Counts) -> Option<Interrupt> {
+        if matches!(&self.terminate, Some(t) if t.is_requested()) {
+            return Some(Interrupt::Cancelled);
+        }
+
+        let exceeded = |limit: Option<u64>, count: u64, start: u64| matches!(limit, Some(limit) if count - start >= limit);
+        if exceeded(
+            self.limits.conflicts,
+            counts.conflicts,
+            self.start.conflicts,
+        ) {
+            Some(Interrupt::Conflicts)
+        } else if exceeded(
+            self.limits.propagations,
+            counts.propagations,
+            self.start.propagations,
+        ) {
+            Some(Interrupt::Propagations)
+        } else if exceeded(
+            self.limits.decisions,
+            counts.decisions,
+            self.start.decisions,
+        ) {
+            Some(Interrupt::Decisions)
+        } else if matches!(self.deadline, Some(d) if Instant::now() >= d) {
+            Some(Interrupt::Timeout)
+        } else {
+            None
+        }
+    }
+}
@@ -1,260 +1,1152 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
 use std::iter;
+use std::time::Instant;
 
 use crate::assignment::{Assignment, Truth};
-use crate::cnf::{Clause, Literal, CNF};
+use crate::cnf::{
+    Clause, ClauseID, ClauseIDGenerator, Literal, NameConflict, NameTable, Variable, CNF,
+};
+use crate::proof::{DratWriter, LratWriter};
+use crate::solver::budget::{Budget, Counts};
+use crate::solver::watch::{self, Propagate, Watches};
+use crate::solver::{self, Interrupt, Limits, Section, SolveResult, Statistics, Status, Terminate};
 
-use log::debug;
+use log::{debug, info};
 
+mod clause_db;
+mod config;
 mod implication_graph;
 mod level;
-use implication_graph::{Decision, ImplicationGraph};
+mod lit;
+mod random;
+mod restart;
+mod var_order;
+use clause_db::{ClauseDB, ClauseRef};
+pub use config::{Config, ParseRestartError, Phase, Reduce, Restart};
+use implication_graph::ImplicationGraph;
 use level::Level;
+use lit::{Lit, Var, VarTable};
+use random::Random;
+use restart::Restarter;
+use var_order::VarOrder;
 
 enum DecideResult {
     Satisfied { assignment: Assignment },
-    Decided { assignment: Assignment },
+    Decided,
+    FailedAssumption { literal: Lit },
 }
 
 enum DeduceResult {
-    Success(Assignment),
-    Conflict,
+    Success,
+    Conflict { clause: ClauseRef },
 }
 
-enum DiagnoseResult {
-    NeedsBackjump { backjump_level: Level },
-    Asserted,
+struct Diagnosis {
+    learned: Vec<Lit>,
+    lbd: usize,
+    backjump_level: Level,
+    hints: Vec<ClauseID>,
+}
+
+enum ProofLog {
+    Drat(DratWriter<Box<dyn Write>>),
+    Lrat(LratWriter<Box<dyn Write>>),
 }
 
 enum SearchResult {
     Satisfiable { model: Assignment },
-    Conflict { backjump_level: Level },
+    Unsatisfiable,
+    FailedAssumption { literal: Lit },
+    Unknown { interrupt: Interrupt },
 }
 
+/// A CDCL solver that keeps its state between calls.
+///
+/// Clauses can be added between calls to `solve_with_assumptions`; learned
+/// clauses, activities and saved phases are reused by the following calls.
 pub struct Solver {
+    config: Config,
+    limits: Limits,
+    terminate: Option<Terminate>,
+    vars: VarTable,
+    /// Names of the variables of the last CNF added, for their DIMACS numbers in proofs.
+    names: NameTable,
+    clauses: ClauseDB,
+    clause_ids: ClauseIDGenerator,
+    unit_ids: Vec<Option<ClauseID>>,
+    watches: Watches<ClauseRef>,
     implication_graph: ImplicationGraph,
-    learned_clauses: Vec<Clause>,
+    propagation_head: usize,
+    var_order: VarOrder,
+    phases: Vec<bool>,
+    target_phases: Vec<bool>,
+    target_size: usize,
+    random: Random,
+    restarter: Restarter,
+    lbd_counter: LbdCounter,
+    next_reduce: u64,
+    reduce_interval: u64,
+    seen: Vec<bool>,
+    analyze_clear: Vec<Var>,
+    assumptions: Vec<Lit>,
+    failed_assumptions: Vec<Literal>,
+    model: Option<Assignment>,
+    proof: Option<ProofLog>,
+    proof_error: Option<io::Error>,
+    is_unsatisfiable: bool,
+    statistics: Statistics,
 }
 
 impl Solver {
-    fn new() -> Self {
+    pub fn new(config: Config) -> Self {
+        let random = Random::new(config.seed);
+        let restarter = Restarter::new(config.restart);
+        let next_reduce = config.reduce.first;
         Solver {
+            config,
+            limits: Limits::default(),
+            terminate: None,
+            vars: VarTable::new(),
+            names: NameTable::new(),
+            clauses: ClauseDB::new(CLAUSE_DECAY),
+            clause_ids: ClauseIDGenerator::new(),
+            unit_ids: Vec::new(),
+            watches: Watches::new(),
             implication_graph: ImplicationGraph::new(),
-            learned_clauses: Vec::new(),
+            propagation_head: 0,
+            var_order: VarOrder::new(VAR_DECAY),
+            phases: Vec::new(),
+            target_phases: Vec::new(),
+            target_size: 0,
+            random,
+            restarter,
+            lbd_counter: LbdCounter::new(),
+            next_reduce,
+            reduce_interval: next_reduce,
+            seen: Vec::new(),
+            analyze_clear: Vec::new(),
+            assumptions: Vec::new(),
+            failed_assumptions: Vec::new(),
+            model: None,
+            proof: None,
+            proof_error: None,
+            is_unsatisfiable: false,
+            statistics: Statistics::default(),
         }
     }
 
-    fn search(&mut self, input_cnf: CNF, level: Level) -> SearchResult {
-        let mut cnf = input_cnf.clone();
-        let mut decided_assignment = match self.decide(&mut cnf, level) {
-            DecideResult::Decided { assignment } => assignment,
-            DecideResult::Satisfied { assignment } => {
-                return SearchResult::Satisfiable { model: assignment }
+    pub fn from_cnf(cnf: &CNF, config: Config) -> Self {
+        let mut solver = Solver::new(config);
+        // a new solver has no names to conflict with
+        solver.add_cnf(cnf).unwrap();
+        solver
+    }
+
+    /// Add the clauses of the CNF in their original order.
+    ///
+    /// Variables that are new to the solver start with the polarity that
+    /// occurs more often in the CNF. Variables are identified by index, so
+    /// the CNF is rejected if it names a variable of the solver differently,
+    /// as CNFs parsed separately may do.
+    pub fn add_cnf(&mut self, cnf: &CNF) -> Result<(), NameConflict> {
+        self.names.merge(cnf.names())?;
+        let num_vars = self.vars.len();
+        let clauses: Vec<_> = cnf.original_clauses().collect();
+        let mut occurrences = HashMap::new();
+        for c in &clauses {
+            for l in c.literals() {
+                let lit = self.intern(l);
+                *occurrences.entry(lit).or_insert(0usize) += 1;
             }
-        };
+        }
+
+        for var in self.vars.vars().skip(num_vars) {
+            let count = |lit| occurrences.get(&lit).copied().unwrap_or(0);
+            let phase = count(Lit::new(var, false)) >= count(Lit::new(var, true));
+            self.phases[var.index()] = phase;
+            self.target_phases[var.index()] = phase;
+        }
+
+        // the original clauses are numbered first, before any clause derived from them
+        let ids: Vec<_> = clauses.iter().map(|_| self.clause_ids.next()).collect();
+        for (c, id) in clauses.into_iter().zip(ids) {
+            self.add_clause_with_id(c, id);
+        }
+        Ok(())
+    }
+
+    /// Write a DRAT proof of the learned and deleted clauses from now on.
+    ///
+    /// Variables are written with their DIMACS numbers, so they must come from
+    /// a CNF made by `dimacs::parse`. Clauses shortened while being added are logged too,
+    /// so the proof is best set before adding any clause.
+    pub fn set_proof(&mut self, proof: DratWriter<Box<dyn Write>>) {
+        self.proof = Some(ProofLog::Drat(proof));
+    }
+
+    /// Write an LRAT proof, where each learned clause lists the clauses it is resolved from.
+    ///
+    /// Clause IDs count the clauses from 1 in the order they are added, which
+    /// is the order of the DIMACS file for clauses added by `add_cnf`. It must
+    /// be set before adding any clause.
+    pub fn set_lrat_proof(&mut self, proof: LratWriter<Box<dyn Write>>) {
+        self.proof = Some(ProofLog::Lrat(proof));
+    }
+
+    /// Flush the proof, reporting the first error that occurred while writing it.
+    pub fn finish_proof(&mut self) -> io::Result<()> {
+        if let Some(e) = self.proof_error.take() {
+            return Err(e);
+        }
+        match &mut self.proof {
+            Some(ProofLog::Drat(proof)) => proof.flush(),
+            Some(ProofLog::Lrat(proof)) => proof.flush(),
+            None => Ok(()),
+        }
+    }
+
+    /// Statistics accumulated over all calls to `solve_with_assumptions`.
+    pub fn statistics(&self) -> &Statistics {
+        &self.statistics
+    }
+
+    /// The model found by the last call to `solve_with_assumptions`, if it was satisfiable.
+    pub fn model(&self) -> Option<&Assignment> {
+        self.model.as_ref()
+    }
+
+    /// The assumptions responsible for the unsatisfiability found by the last call.
+    ///
+    /// Empty if the clauses are unsatisfiable regardless of assumptions.
+    pub fn failed_assumptions(&self) -> &[Literal] {
+        &self.failed_assumptions
+    }
+
+    /// The value of `variable` in the model found by the last call.
+    pub fn value(&self, variable: &Variable) -> Option<Truth> {
+        self.model.as_ref().and_then(|m| m.get(variable))
+    }
+
+    pub fn add_clause(&mut self, clause: &Clause) {
+        let id = self.clause_ids.next();
+        self.add_clause_with_id(clause, id);
+    }
+
+    /// Limit each following call to `solve_with_assumptions`.
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    /// Stop each following call to `solve_with_assumptions` once `terminate`
+    /// is requested.
+    pub fn set_terminate(&mut self, terminate: Option<Terminate>) {
+        self.terminate = terminate;
+    }
+
+    pub fn solve(&mut self) -> Status {
+        self.solve_with_assumptions(&[])
+    }
+
+    /// Solve under the given assumptions, which only hold for this call.
+    ///
+    /// Returns whether the clauses are satisfiable together with the
+    /// assumptions, in which case the model is available from `model`, or
+    /// the limit that stopped the search.
+    pub fn solve_with_assumptions(&mut self, assumptions: &[Literal]) -> Status {
+        self.backjump(Level::initial());
+        self.model = None;
+        self.failed_assumptions.clear();
+        if self.is_unsatisfiable {
+            return Status::Unsatisfiable;
+        }
+
+        self.assumptions = assumptions.iter().map(|l| self.intern(l)).collect();
+        let budget = Budget::start(self.limits, self.terminate.clone(), self.counts());
+        let start = Instant::now();
+        let result = self.search(&budget);
+        self.statistics.times.total += start.elapsed();
+        self.assumptions.clear();
+
+        match result {
+            SearchResult::Satisfiable { model } => {
+                self.model = Some(model);
+                Status::Satisfiable
+            }
+            SearchResult::Unsatisfiable => {
+                self.is_unsatisfiable = true;
+                Status::Unsatisfiable
+            }
+            SearchResult::Unknown { interrupt } => {
+                info!("UNKNOWN: {}", interrupt);
+                self.backjump(Level::initial());
+                Status::Unknown(interrupt)
+            }
+            SearchResult::FailedAssumption { literal } => {
+                debug!("FAILED ASSUMPTION: {}", self.vars.literal(literal));
+                self.failed_assumptions = self
+                    .analyze_final(literal)
+                    .into_iter()
+                    .map(|l| self.vars.literal(l))
+                    .collect();
+                Status::Unsatisfiable
+            }
+        }
+    }
 
+    fn counts(&self) -> Counts {
+        Counts {
+            conflicts: self.statistics.conflicts,
+            propagations: self.statistics.propagations,
+            decisions: self.statistics.decisions,
+        }
+    }
+
+    fn add_clause_with_id(&mut self, clause: &Clause, mut id: ClauseID) {
+        self.backjump(Level::initial());
+        self.model = None;
+
+        let mut literals = Vec::new();
+        let mut falsified = Vec::new();
+        for l in clause.literals() {
+            let lit = self.intern(l);
+            match self.implication_graph.literal_value(lit) {
+                // satisfied at the initial level
+                Some(true) => return,
+                Some(false) => falsified.push(lit),
+                None => literals.push(lit),
+            }
+        }
+        if literals.iter().any(|l| literals.contains(&!*l)) {
+            // tautology
+            return;
+        }
+        if !falsified.is_empty() {
+            let hints: Vec<_> = falsified
+                .iter()
+                .filter_map(|l| self.unit_ids[l.var().index()])
+                .chain(iter::once(id))
+                .collect();
+            id = self.clause_ids.next();
+            self.log_addition(id, &literals, &hints);
+        }
+
+        match literals.as_slice() {
+            [] => self.is_unsatisfiable = true,
+            [unit] => {
+                self.assign(*unit, None);
+                self.unit_ids[unit.var().index()] = Some(id);
+            }
+            _ => {
+                let cref = self.clauses.add(id, literals);
+                self.watch_clause(cref);
+            }
+        }
+    }
+
+    fn intern(&mut self, literal: &Literal) -> Lit {
+        let lit = self.vars.intern_literal(literal);
+        let num_vars = self.vars.len();
+        if num_vars > self.phases.len() {
+            self.watches.grow(num_vars);
+            self.implication_graph.grow(num_vars);
+            self.seen.resize(num_vars, false);
+            self.unit_ids.resize(num_vars, None);
+            self.var_order.grow(num_vars);
+            self.phases.resize(num_vars, true);
+            self.target_phases.resize(num_vars, true);
+        }
+        lit
+    }
+
+    fn watch_clause(&mut self, cref: ClauseRef) {
+        let clause = &self.clauses[cref];
+        self.watches.watch(clause[0], cref);
+        self.watches.watch(clause[1], cref);
+    }
+
+    fn assign(&mut self, lit: Lit, reason: Option<ClauseRef>) {
+        self.implication_graph
+            .make_decision(lit.var(), Truth::from(!lit.is_negated()), reason);
+
+        // LRAT hints refer to units at the initial level rather than to their reasons
+        if let Some(cref) = reason {
+            if self.implication_graph.level() == Level::initial() && self.is_lrat() {
+                let hints = self.lrat_hints(cref, &[lit]);
+                let id = self.clause_ids.next();
+                self.log_addition(id, &[lit], &hints);
+                self.unit_ids[lit.var().index()] = Some(id);
+            }
+        }
+    }
+
+    fn backjump(&mut self, level: Level) {
+        for lit in self.implication_graph.assigned_since(level.next()) {
+            self.var_order.insert(lit.var());
+            self.phases[lit.var().index()] = !lit.is_negated();
+        }
+        self.implication_graph.erase(level.next());
+        self.propagation_head = self
+            .propagation_head
+            .min(self.implication_graph.trail().len());
+    }
+
+    fn search(&mut self, budget: &Budget) -> SearchResult {
         loop {
-            match self.deduce(&mut cnf, level) {
-                DeduceResult::Success(deduced_assignment) => {
-                    match self.search(cnf, level.next()) {
-                        SearchResult::Satisfiable { mut model } => {
-                            model.extend(decided_assignment);
-                            model.extend(deduced_assignment);
-                            return SearchResult::Satisfiable { model };
+            if let Some(interrupt) = budget.check(self.counts()) {
+                return SearchResult::Unknown { interrupt };
+            }
+
+            let start = Instant::now();
+            let deduced = self.deduce();
+            self.statistics.times.propagation += start.elapsed();
+
+            match deduced {
+                DeduceResult::Conflict { clause } => {
+                    if self.implication_graph.level() == Level::initial() {
+                        let hints = if self.is_lrat() {
+                            self.lrat_hints(clause, &[])
+                        } else {
+                            Vec::new()
+                        };
+                        let id = self.clause_ids.next();
+                        self.log_addition(id, &[], &hints);
+                        return SearchResult::Unsatisfiable;
+                    }
+
+                    self.update_target_phases();
+                    let start = Instant::now();
+                    let diagnosis = self.diagnose(clause);
+                    self.statistics.times.analysis += start.elapsed();
+                    self.backjump(diagnosis.backjump_level);
+                    self.assert_learned_clause(diagnosis.learned, diagnosis.lbd, &diagnosis.hints);
+
+                    if self.statistics.conflicts >= self.next_reduce {
+                        let start = Instant::now();
+                        self.reduce_clause_db();
+                        self.statistics.times.reduction += start.elapsed();
+                    }
+                }
+                DeduceResult::Success if self.restarter.should_restart() => self.restart(),
+                DeduceResult::Success => {
+                    let start = Instant::now();
+                    let decided = self.decide();
+                    self.statistics.times.decision += start.elapsed();
+
+                    match decided {
+                        DecideResult::Satisfied { assignment } => {
+                            return SearchResult::Satisfiable { model: assignment };
                         }
-                        SearchResult::Conflict { backjump_level } => {
-                            if backjump_level != level {
-                                self.implication_graph.erase(level);
-                                // skipping (keep backjumping)
-                                return SearchResult::Conflict { backjump_level };
-                            }
-                            // resume from backjumping
+                        DecideResult::FailedAssumption { literal } => {
+                            return SearchResult::FailedAssumption { literal };
+                        }
+                        DecideResult::Decided => {
+                            let level = self.implication_graph.level().index();
+                            let max_level = &mut self.statistics.max_decision_level;
+                            *max_level = (*max_level).max(level);
                         }
                     }
                 }
-                DeduceResult::Conflict => match self.diagnose(&cnf, level) {
-                    DiagnoseResult::NeedsBackjump { backjump_level } => {
-                        self.implication_graph.erase(level);
-                        return SearchResult::Conflict { backjump_level };
-                    }
-                    DiagnoseResult::Asserted => (),
-                },
-            }
-
-            decided_assignment.clear();
-            self.implication_graph.erase(level);
-            cnf = input_cnf.clone();
-            for c in &self.learned_clauses {
-                cnf.add_clause(c.clone());
             }
         }
     }
 
-    fn decide(&mut self, cnf: &mut CNF, level: Level) -> DecideResult {
-        let mut assignment = Assignment::new();
+    fn restart(&mut self) {
+        debug!("RESTART");
+        self.backjump(Level::initial());
+        self.restarter.on_restart();
+        self.target_size = 0;
+        self.statistics.restarts += 1;
+    }
 
-        let literal = match choose_literal(cnf) {
+    fn decide(&mut self) -> DecideResult {
+        // assumptions are decided first, one per level
+        while self.implication_graph.level().index() < self.assumptions.len() {
+            let literal = self.assumptions[self.implication_graph.level().index()];
+            match self.implication_graph.literal_value(literal) {
+                Some(true) => {
+                    self.implication_graph.new_level();
+                }
+                Some(false) => return DecideResult::FailedAssumption { literal },
+                None => {
+                    debug!("DECIDE: Assumed {}", self.vars.literal(literal));
+                    self.implication_graph.new_level();
+                    self.assign(literal, None);
+                    return DecideResult::Decided;
+                }
+            }
+        }
+
+        let literal = match self.choose_literal() {
             Some(l) => l,
-            None => return DecideResult::Satisfied { assignment },
+            None => {
+                let mut assignment = Assignment::new();
+                for var in self.vars.vars() {
+                    let truth = self.implication_graph.value(var).unwrap();
+                    assignment.assign(self.vars.variable(var), truth);
+                }
+                return DecideResult::Satisfied { assignment };
+            }
         };
 
-        self.implication_graph.make_decision(
-            literal.variable(),
-            Truth::from(!literal.is_negated()),
-            level,
-            iter::empty(),
-        );
-
-        assignment.assign_true(&literal);
-        cnf.simplify_true_literal(&literal);
+        debug!("DECIDE: Decided {}", self.vars.literal(literal));
+        self.statistics.decisions += 1;
+        self.implication_graph.new_level();
+        self.assign(literal, None);
+        DecideResult::Decided
+    }
 
-        if cnf.is_empty() {
-            return DecideResult::Satisfied { assignment };
+    fn choose_literal(&mut self) -> Option<Lit> {
+        while let Some(var) = self.var_order.pop() {
+            if self.implication_graph.value(var).is_none() {
+                let positive = self.polarity(var);
+                return Some(Lit::new(var, !positive));
+            }
         }
+        None
+    }
 
-        debug!("DECIDE: Decided {}", assignment);
-        DecideResult::Decided { assignment }
+    fn polarity(&mut self, var: Var) -> bool {
+        match self.config.phase {
+            Phase::False => false,
+            Phase::True => true,
+            Phase::Saved => self.phases[var.index()],
+            Phase::Random => self.random.next_bool(),
+            Phase::Target => self.target_phases[var.index()],
+        }
     }
 
-    fn deduce(&mut self, cnf: &mut CNF, level: Level) -> DeduceResult {
-        if cnf.has_empty_clause() {
-            return DeduceResult::Conflict;
+    /// Remember the assignment below the conflicting level if it is the largest
+    /// conflict-free one so far.
+    fn update_target_phases(&mut self) {
+        let level = self.implication_graph.level();
+        let consistent = self.implication_graph.assigned_below(level);
+        if consistent.len() <= self.target_size {
+            return;
         }
 
-        match self.unit_propagation(cnf, level) {
-            Some(assignment) => {
-                debug!("DEDUCE: SUCCESSS {}", assignment);
-                DeduceResult::Success(assignment)
-            }
+        self.target_size = consistent.len();
+        for lit in consistent {
+            self.target_phases[lit.var().index()] = !lit.is_negated();
+        }
+    }
+
+    fn deduce(&mut self) -> DeduceResult {
+        match self.unit_propagation() {
             None => {
+                debug!("DEDUCE: SUCCESSS");
+                DeduceResult::Success
+            }
+            Some(clause) => {
                 debug!("DEDUCE: CONFLICT");
-                DeduceResult::Conflict
+                DeduceResult::Conflict { clause }
             }
         }
     }
 
-    fn unit_propagation(&mut self, cnf: &mut CNF, level: Level) -> Option<Assignment> {
-        let (id, c) = match cnf.unit_clauses().with_id().next() {
-            Some(x) => x,
-            None => return Some(Assignment::new()),
-        };
-        let unit_literal = c.unit().unwrap().clone();
-
-        // TODO: Refactor: remove collect() call
-        let implicants: HashSet<_> = cnf
-            .get_from_db(id)
-            .unwrap()
-            .literals()
-            .filter_map(|cause| {
-                if *cause == unit_literal {
-                    return None;
+    /// Propagate the assignments on the trail through the watch lists,
+    /// returning the conflicting clause if any.
+    fn unit_propagation(&mut self) -> Option<ClauseRef> {
+        while self.propagation_head < self.implication_graph.trail().len() {
+            let lit = self.implication_graph.trail()[self.propagation_head];
+            self.propagation_head += 1;
+
+            if let Some(conflict) = watch::propagate(self, !lit) {
+                self.propagation_head = self.implication_graph.trail().len();
+                return Some(conflict);
+            }
+        }
+
+        None
+    }
+
+    /// First-UIP conflict analysis.
+    ///
+    /// Resolve the conflicting clause with the reasons of the current-level
+    /// literals in reverse trail order until a single current-level literal is
+    /// left. Returns the minimized clause, asserting literal first, with the
+    /// level to backjump to.
+    fn diagnose(&mut self, conflict: ClauseRef) -> Diagnosis {
+        let level = self.implication_graph.level();
+        let mut learned = Vec::new();
+        let mut pending = 0;
+        let mut index = self.implication_graph.trail().len();
+        let mut clause = conflict;
+        let mut skip = 0;
+
+        let uip = loop {
+            if self.clauses.is_learned(clause) {
+                let lbd = self
+                    .lbd_counter
+                    .count(&self.implication_graph, &self.clauses[clause]);
+                self.clauses.mark_used(clause, lbd);
+                self.clauses.bump(clause);
+            }
+
+            for &q in &self.clauses[clause][skip..] {
+                let var = q.var();
+                if self.seen[var.index()] {
+                    continue;
+                }
+                let q_level = self.implication_graph.level_of(var).unwrap();
+                if q_level == Level::initial() {
+                    continue;
                 }
 
-                let decision = self
-                    .implication_graph
-                    .find_decision(cause.variable(), Truth::from(cause.is_negated()))
-                    .unwrap();
-                Some(decision)
-            })
-            .collect();
-        self.implication_graph.make_decision(
-            unit_literal.variable(),
-            Truth::from(!unit_literal.is_negated()),
-            level,
-            implicants,
-        );
+                self.seen[var.index()] = true;
+                self.var_order.bump(var);
+                if q_level == level {
+                    pending += 1;
+                } else {
+                    learned.push(q);
+                    self.analyze_clear.push(var);
+                }
+            }
+
+            let p = loop {
+                index -= 1;
+                let p = self.implication_graph.trail()[index];
+                if self.seen[p.var().index()] {
+                    break p;
+                }
+            };
+            self.seen[p.var().index()] = false;
+            pending -= 1;
+            if pending == 0 {
+                break p;
+            }
+
+            // the implied literal always comes first in its reason
+            clause = self.implication_graph.reason(p.var()).unwrap();
+            skip = 1;
+        };
+        learned.insert(0, !uip);
+
+        self.minimize(&mut learned);
+        for var in self.analyze_clear.drain(..) {
+            self.seen[var.index()] = false;
+        }
 
-        cnf.simplify_true_literal(&unit_literal);
-        if cnf.has_empty_clause() {
-            None
+        // watch the literal with the highest level after the asserting one
+        let mut backjump_level = Level::initial();
+        for i in 1..learned.len() {
+            let l = self.implication_graph.level_of(learned[i].var()).unwrap();
+            if l > backjump_level {
+                backjump_level = l;
+                learned.swap(1, i);
+            }
+        }
+        debug!("DIAGNOSE: NeedsBackjump {:?}", backjump_level);
+
+        let hints = if self.is_lrat() {
+            self.lrat_hints(conflict, &learned)
         } else {
-            self.unit_propagation(cnf, level)
-                .map(|a| a.assigned_true(&unit_literal))
+            Vec::new()
+        };
+
+        let lbd = self.lbd_counter.count(&self.implication_graph, &learned);
+        self.restarter.on_conflict(lbd);
+        self.var_order.decay();
+        self.clauses.decay();
+        self.statistics.conflicts += 1;
+        self.statistics.learned_clauses += 1;
+        self.statistics.learned_literals += learned.len() as u64;
+        self.statistics.learned_lbd += lbd as u64;
+        Diagnosis {
+            learned,
+            lbd,
+            backjump_level,
+            hints,
         }
     }
 
-    fn diagnose(&mut self, cnf: &CNF, level: Level) -> DiagnoseResult {
-        let preds = cnf.empty_clauses().with_id().flat_map(|(id, _)| {
-            cnf.get_from_db(id).unwrap().literals().map(|l| {
-                self.implication_graph
-                    .find_decision(l.variable(), Truth::from(l.is_negated()))
-                    .unwrap()
-            })
+    /// Collect the assumptions that imply the negation of the failed assumption.
+    ///
+    /// Walks the trail backwards from the conflict like `diagnose` does, but
+    /// keeps every decision reached, all of which are assumptions.
+    fn analyze_final(&mut self, failed: Lit) -> Vec<Lit> {
+        let mut assumptions = vec![failed];
+        if self.implication_graph.level_of(failed.var()) == Some(Level::initial()) {
+            return assumptions;
+        }
+
+        self.seen[failed.var().index()] = true;
+        let start = self
+            .implication_graph
+            .assigned_below(Level::initial().next())
+            .len();
+        for index in (start..self.implication_graph.trail().len()).rev() {
+            let lit = self.implication_graph.trail()[index];
+            let var = lit.var();
+            if !self.seen[var.index()] {
+                continue;
+            }
+
+            match self.implication_graph.reason(var) {
+                Some(cref) => {
+                    for q in &self.clauses[cref][1..] {
+                        if self.implication_graph.level_of(q.var()) != Some(Level::initial()) {
+                            self.seen[q.var().index()] = true;
+                        }
+                    }
+                }
+                None => assumptions.push(lit),
+            }
+            self.seen[var.index()] = false;
+        }
+        assumptions
+    }
+
+    /// Remove literals of the learned clause that are implied by the others.
+    ///
+    /// Local minimization drops a literal when all antecedents in its reason are
+    /// already in the clause; recursive minimization follows the reasons further
+    /// down, but only through levels that occur in the clause.
+    fn minimize(&mut self, learned: &mut Vec<Lit>) {
+        let size = learned.len();
+
+        let mut kept = 1;
+        for i in 1..learned.len() {
+            if !self.is_locally_redundant(learned[i]) {
+                learned[kept] = learned[i];
+                kept += 1;
+            }
+        }
+        learned.truncate(kept);
+
+        let levels = learned[1..].iter().fold(0, |acc, l| {
+            acc | abstract_level(self.implication_graph.level_of(l.var()).unwrap())
         });
-        let conflict_causes = self.find_conflict_causes(preds, level);
-        let backjump_level = compute_backjump_level(conflict_causes.iter());
-        let induced_clause = make_induced_clause(conflict_causes.iter());
+        let mut kept = 1;
+        for i in 1..learned.len() {
+            if !self.is_recursively_redundant(learned[i], levels) {
+                learned[kept] = learned[i];
+                kept += 1;
+            }
+        }
+        learned.truncate(kept);
+
+        self.statistics.minimized_literals += (size - learned.len()) as u64;
+    }
+
+    fn is_locally_redundant(&self, lit: Lit) -> bool {
+        match self.implication_graph.reason(lit.var()) {
+            Some(cref) => self.clauses[cref][1..].iter().all(|q| {
+                self.seen[q.var().index()]
+                    || self.implication_graph.level_of(q.var()) == Some(Level::initial())
+            }),
+            None => false,
+        }
+    }
+
+    fn is_recursively_redundant(&mut self, lit: Lit, levels: u64) -> bool {
+        if self.implication_graph.reason(lit.var()).is_none() {
+            return false;
+        }
+
+        let top = self.analyze_clear.len();
+        let mut stack = vec![lit];
+        while let Some(p) = stack.pop() {
+            let cref = self.implication_graph.reason(p.var()).unwrap();
+            for &q in &self.clauses[cref][1..] {
+                let var = q.var();
+                let q_level = self.implication_graph.level_of(var).unwrap();
+                if self.seen[var.index()] || q_level == Level::initial() {
+                    continue;
+                }
+
+                if self.implication_graph.reason(var).is_some()
+                    && abstract_level(q_level) & levels != 0
+                {
+                    self.seen[var.index()] = true;
+                    self.analyze_clear.push(var);
+                    stack.push(q);
+                } else {
+                    for var in self.analyze_clear.drain(top..) {
+                        self.seen[var.index()] = false;
+                    }
+                    return false;
+                }
+            }
+        }
+        true
+    }
 
-        self.learn(induced_clause);
+    /// Learn the clause and assign its asserting literal, which must be first.
+    fn assert_learned_clause(&mut self, clause: Vec<Lit>, lbd: usize, hints: &[ClauseID]) {
+        debug!(
+            "LEARN: {}",
+            Clause::from_literals(clause.iter().map(|l| self.vars.literal(*l)))
+        );
+        let id = self.clause_ids.next();
+        self.log_addition(id, &clause, hints);
 
-        if backjump_level != level || level == Level::initial() {
-            debug!("DIAGNOSE: NeedsBackjump {:?}", backjump_level);
-            DiagnoseResult::NeedsBackjump { backjump_level }
+        let asserting = clause[0];
+        if clause.len() >= 2 {
+            let cref = self.clauses.add_learned(id, clause, lbd);
+            self.watch_clause(cref);
+            self.assign(asserting, Some(cref));
         } else {
-            debug!("DIAGNOSE: Asserted");
-            DiagnoseResult::Asserted
-        }
-    }
-
-    fn learn(&mut self, clause: Clause) {
-        debug!("LEARN: {}", &clause);
-        self.learned_clauses.push(clause);
-    }
-
-    fn find_conflict_causes<I>(&self, direct_causes: I, level: Level) -> HashSet<Decision>
-    where
-        I: IntoIterator<Item = Decision>,
-    {
-        let mut causes = HashSet::new();
-        for d in direct_causes.into_iter() {
-            let preds = self.implication_graph.predecessors(&d);
-            if preds.is_empty() {
-                causes.insert(d);
-            } else {
-                for pre in preds {
-                    let pred_level = pre.level();
-                    if pred_level == level {
-                        let pre_causes = self.find_conflict_causes(iter::once(pre), pred_level);
-                        causes.extend(pre_causes);
-                    } else {
-                        assert!(pred_level < level);
-                        causes.insert(pre);
+            self.assign(asserting, None);
+            self.unit_ids[asserting.var().index()] = Some(id);
+        }
+    }
+
+    fn is_lrat(&self) -> bool {
+        matches!(self.proof, Some(ProofLog::Lrat(_)))
+    }
+
+    /// IDs of the clauses that derive `clause` by unit propagation, given that
+    /// `conflict` is falsified by its negation.
+    ///
+    /// Units of the initial level come first, then the reasons of the other
+    /// literals involved in trail order, and the conflicting clause last.
+    fn lrat_hints(&mut self, conflict: ClauseRef, clause: &[Lit]) -> Vec<ClauseID> {
+        let in_clause: HashSet<_> = clause.iter().map(|l| l.var()).collect();
+        let mut pending = 0;
+        for &q in self.clauses[conflict].iter() {
+            let var = q.var();
+            if !in_clause.contains(&var) && !self.seen[var.index()] {
+                self.seen[var.index()] = true;
+                pending += 1;
+            }
+        }
+
+        let mut units = Vec::new();
+        let mut reasons = Vec::new();
+        let mut index = self.implication_graph.trail().len();
+        while pending > 0 {
+            index -= 1;
+            let var = self.implication_graph.trail()[index].var();
+            if !self.seen[var.index()] {
+                continue;
+            }
+            self.seen[var.index()] = false;
+            pending -= 1;
+
+            if let Some(id) = self.unit_ids[var.index()] {
+                units.push(id);
+                continue;
+            }
+            let cref = self.implication_graph.reason(var).unwrap();
+            reasons.push(self.clauses.id(cref));
+            for &q in &self.clauses[cref][1..] {
+                let var = q.var();
+                if !in_clause.contains(&var) && !self.seen[var.index()] {
+                    self.seen[var.index()] = true;
+                    pending += 1;
+                }
+            }
+        }
+
+        units.extend(reasons.into_iter().rev());
+        units.push(self.clauses.id(conflict));
+        units
+    }
+
+    fn log_addition(&mut self, id: ClauseID, clause: &[Lit], hints: &[ClauseID]) {
+        if self.proof.is_none() || self.proof_error.is_some() {
+            return;
+        }
+
+        let literals = match self.dimacs_literals(clause) {
+            Ok(literals) => literals,
+            Err(e) => {
+                self.proof_error = Some(e);
+                return;
+            }
+        };
+        let result = match self.proof.as_mut().unwrap() {
+            ProofLog::Drat(proof) => proof.add(&literals),
+            ProofLog::Lrat(proof) => {
+                let hints: Vec<_> = hints.iter().map(|&h| lrat_id(h) as i64).collect();
+                proof.add(lrat_id(id), &literals, &hints)
+            }
+        };
+        self.proof_error = result.err();
+    }
+
+    fn log_deletion(&mut self, cref: ClauseRef) {
+        if self.proof.is_none() || self.proof_error.is_some() {
+            return;
+        }
+
+        let literals = match self.dimacs_literals(&self.clauses[cref]) {
+            Ok(literals) => literals,
+            Err(e) => {
+                self.proof_error = Some(e);
+                return;
+            }
+        };
+        let result = match self.proof.as_mut().unwrap() {
+            ProofLog::Drat(proof) => proof.delete(&literals),
+            ProofLog::Lrat(proof) => proof.delete(&[lrat_id(self.clauses.id(cref))]),
+        };
+        self.proof_error = result.err();
+    }
+
+    fn dimacs_literals(&self, clause: &[Lit]) -> io::Result<Vec<i64>> {
+        clause
+            .iter()
+            .map(|lit| {
+                let variable = *self.vars.variable(lit.var());
+                match self.names.dimacs_number(variable) {
+                    Some(n) if lit.is_negated() => Ok(-i64::from(n)),
+                    Some(n) => Ok(i64::from(n)),
+                    None => {
+                        let message = format!(
+                            "variable {} has no DIMACS number",
+                            self.names.display(&variable)
+                        );
+                        Err(io::Error::new(io::ErrorKind::InvalidInput, message))
                     }
                 }
+            })
+            .collect()
+    }
+
+    /// Whether the clause is the reason of a current assignment.
+    fn is_locked(&self, cref: ClauseRef) -> bool {
+        let first = self.clauses[cref][0];
+        self.implication_graph.reason(first.var()) == Some(cref)
+    }
+
+    /// Drop the less useful learned clauses.
+    ///
+    /// Clauses with LBD up to `core_lbd` are kept forever, and those up to
+    /// `tier2_lbd` as long as they took part in a conflict since the last
+    /// reduction. Of the rest, the `fraction` with the lowest activity is removed.
+    fn reduce_clause_db(&mut self) {
+        let Reduce {
+            increment,
+            core_lbd,
+            tier2_lbd,
+            fraction,
+            ..
+        } = self.config.reduce;
+
+        let mut candidates = Vec::new();
+        for cref in self.clauses.learned().to_vec() {
+            let lbd = self.clauses.lbd(cref);
+            let used = self.clauses.take_used(cref);
+            if lbd <= core_lbd || (lbd <= tier2_lbd && used) || self.is_locked(cref) {
+                continue;
             }
+            candidates.push(cref);
+        }
+
+        let clauses = &self.clauses;
+        candidates.sort_by(|&c1, &c2| {
+            clauses
+                .activity(c1)
+                .partial_cmp(&clauses.activity(c2))
+                .unwrap()
+                .then(clauses.lbd(c2).cmp(&clauses.lbd(c1)))
+        });
+        candidates.truncate((candidates.len() as f64 * fraction) as usize);
+
+        let removed: HashSet<_> = candidates.into_iter().collect();
+        self.watches.retain(|cref| !removed.contains(cref));
+        self.clauses.retain_learned(|cref| !removed.contains(cref));
+        for &cref in &removed {
+            self.log_deletion(cref);
+            self.clauses.remove(cref);
         }
-        causes
+        debug!("REDUCE: removed {} clauses", removed.len());
+
+        if self.clauses.needs_collection() {
+            let relocation = self.clauses.collect_garbage();
+            self.watches.relocate(&relocation);
+            self.implication_graph.relocate(&relocation);
+        }
+
+        self.statistics.deleted_clauses += removed.len() as u64;
+        self.reduce_interval += increment;
+        self.next_reduce = self.statistics.conflicts + self.reduce_interval;
     }
 }
 
-fn choose_literal(cnf: &CNF) -> Option<Literal> {
-    cnf.most_occurred_literal().cloned()
+impl Propagate for Solver {
+    type Literal = Lit;
+    type Clause = ClauseRef;
+
+    fn watches_mut(&mut self) -> &mut Watches<ClauseRef> {
+        &mut self.watches
+    }
+
+    fn clause(&self, cref: ClauseRef) -> &[Lit] {
+        &self.clauses[cref]
+    }
+
+    fn clause_mut(&mut self, cref: ClauseRef) -> &mut [Lit] {
+        &mut self.clauses[cref]
+    }
+
+    fn literal_value(&self, lit: Lit) -> Option<bool> {
+        self.implication_graph.literal_value(lit)
+    }
+
+    fn imply(&mut self, lit: Lit, reason: ClauseRef) {
+        self.statistics.propagations += 1;
+        self.assign(lit, Some(reason));
+    }
 }
 
-fn compute_backjump_level<'a>(causes: impl IntoIterator<Item = &'a Decision>) -> Level {
-    causes.into_iter().map(|d| d.level()).max().unwrap()
+/// Counts the number of distinct levels in a clause, also known as LBD or glue.
+struct LbdCounter {
+    stamps: Vec<u64>,
+    stamp: u64,
 }
 
-fn make_induced_clause<'a>(causes: impl IntoIterator<Item = &'a Decision>) -> Clause {
-    let mut literals = Vec::new();
-    for decision in causes.into_iter() {
-        let literal = Literal::new(decision.variable().clone(), decision.truth().as_bool());
-        literals.push(literal);
+impl LbdCounter {
+    fn new() -> Self {
+        LbdCounter {
+            stamps: Vec::new(),
+            stamp: 0,
+        }
     }
-    Clause::from_literals(literals)
+
+    fn count(&mut self, graph: &ImplicationGraph, clause: &[Lit]) -> usize {
+        let num_levels = graph.level().index() + 1;
+        if self.stamps.len() < num_levels {
+            self.stamps.resize(num_levels, 0);
+        }
+
+        self.stamp += 1;
+        let mut lbd = 0;
+        for lit in clause {
+            let level = graph.level_of(lit.var()).unwrap();
+            if self.stamps[level.index()] != self.stamp {
+                self.stamps[level.index()] = self.stamp;
+                lbd += 1;
+            }
+        }
+        lbd
+    }
+}
+
+const VAR_DECAY: f64 = 0.95;
+const CLAUSE_DECAY: f32 = 0.999;
+
+/// LRAT numbers clauses from 1.
+fn lrat_id(id: ClauseID) -> u64 {
+    id.index() as u64 + 1
+}
+
+/// Set of levels as a bit mask, used to prune the search of recursive minimization.
+fn abstract_level(level: Level) -> u64 {
+    1 << (level.index() % 64)
 }
 
 pub fn solve(cnf: CNF) -> Option<Assignment> {
-    let mut solver = Solver::new();
-    match solver.search(cnf, Level::initial()) {
-        SearchResult::Satisfiable { model } => Some(model),
-        SearchResult::Conflict { .. } => None,
+    let (model, statistics) = solve_with_config(cnf, Config::default());
+    info!("{}", statistics);
+    model
+}
+
+pub fn solve_with_config(cnf: CNF, config: Config) -> (Option<Assignment>, Statistics) {
+    let mut solver = Solver::from_cnf(&cnf, config);
+    solver.solve();
+    (solver.model, solver.statistics)
+}
+
+/// The CDCL solver behind the `solver::Solver` trait, solving each CNF from scratch.
+#[derive(Default)]
+pub struct Engine {
+    config: solver::Config,
+    terminate: Option<Terminate>,
+    statistics: Statistics,
+}
+
+impl Engine {
+    pub fn new(config: solver::Config) -> Engine {
+        Engine {
+            config,
+            terminate: None,
+            statistics: Statistics::default(),
+        }
+    }
+}
+
+impl super::Solver for Engine {
+    fn run(&mut self, cnf: CNF) -> SolveResult {
+        let mut solver = Solver::from_cnf(&cnf, self.config.cdcl.clone());
+        solver.set_limits(self.config.limits);
+        solver.set_terminate(self.terminate.clone());
+        let status = solver.solve();
+        self.statistics = solver.statistics;
+        match status {
+            Status::Satisfiable => SolveResult::Satisfiable(solver.model.unwrap()),
+            Status::Unsatisfiable => SolveResult::Unsatisfiable,
+            Status::Unknown(interrupt) => SolveResult::Unknown(interrupt),
+        }
+    }
+
+    fn statistics(&self) -> &Statistics {
+        &self.statistics
+    }
+
+    fn set_terminate(&mut self, terminate: Option<Terminate>) {
+        self.terminate = terminate;
+    }
+
+    fn config(&self) -> &solver::Config {
+        &self.config
+    }
+
+    fn config_mut(&mut self) -> &mut solver::Config {
+        &mut self.config
+    }
+
+    fn sections(&self) -> &[Section] {
+        &[Section::CDCL, Section::Limits]
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::solve;
+    use std::cell::RefCell;
+    use std::io::{self, Write};
+    use std::rc::Rc;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use super::{solve, solve_with_config, Config, Phase, Reduce, Restart, Solver};
+    use crate::assignment::Truth;
+    use crate::cnf::{Literal, NameTable, CNF};
+    use crate::dimacs;
+    use crate::proof::{self, DratWriter, Encoding, LratWriter};
+    use crate::solver::{Interrupt, Limits, Status, Terminate};
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Pigeonhole principle: `n + 1` pigeons do not fit in `n` holes.
+    fn pigeonhole(n: usize) -> CNF {
+        let mut clauses = Vec::new();
+        for p in 0..=n {
+            let c: Vec<_> = (0..n).map(|h| format!("P{}H{}", p, h)).collect();
+            clauses.push(c.join(" \\/ "));
+        }
+        for h in 0..n {
+            for p1 in 0..=n {
+                for p2 in p1 + 1..=n {
+                    clauses.push(format!("!P{}H{} \\/ !P{}H{}", p1, h, p2, h));
+                }
+            }
+        }
+        clauses.join(" /\\ ").parse().unwrap()
+    }
 
     #[test]
     fn test_excluded_middle() {
@@ -280,4 +1172,225 @@ mod tests {
         )
         .is_none());
     }
+
+    #[test]
+    fn test_learned_clause_statistics() {
+        let (model, statistics) = solve_with_config(
+            "A \\/ B /\\ !A \\/ B /\\ A \\/ !B /\\ !A \\/ !B"
+                .parse()
+                .unwrap(),
+            Config::default(),
+        );
+        assert!(model.is_none());
+        assert!(statistics.conflicts >= 1);
+        assert_eq!(statistics.learned_clauses, statistics.conflicts);
+        assert!(statistics.average_learned_size() <= 1.0);
+        assert!(statistics.average_learned_lbd() <= statistics.average_learned_size());
+        assert_eq!(statistics.max_decision_level, 1);
+        assert!(statistics.times.total >= statistics.times.analysis);
+    }
+
+    #[test]
+    fn test_phases() {
+        let phases = vec![
+            Phase::False,
+            Phase::True,
+            Phase::Saved,
+            Phase::Random,
+            Phase::Target,
+        ];
+        for phase in phases {
+            let config = Config {
+                phase,
+                ..Config::default()
+            };
+            let cnf = "A \\/ B /\\ !A \\/ C /\\ !B \\/ !C".parse().unwrap();
+            assert!(solve_with_config(cnf, config.clone()).0.is_some());
+            let cnf = "A \\/ B /\\ !A \\/ B /\\ A \\/ !B /\\ !A \\/ !B"
+                .parse()
+                .unwrap();
+            assert!(solve_with_config(cnf, config).0.is_none());
+        }
+    }
+
+    #[test]
+    fn test_restarts() {
+        let restarts = vec![
+            Restart::Never,
+            Restart::Luby { unit: 1 },
+            Restart::Geometric {
+                initial: 1,
+                factor: 1.1,
+            },
+            Restart::glucose(),
+        ];
+        for restart in restarts {
+            let config = Config {
+                restart,
+                ..Config::default()
+            };
+            let cnf = "A \\/ B /\\ !A \\/ B /\\ A \\/ !B /\\ !A \\/ !B"
+                .parse()
+                .unwrap();
+            assert!(solve_with_config(cnf, config).0.is_none());
+        }
+    }
+
+    #[test]
+    fn test_reduce() {
+        let config = Config {
+            reduce: Reduce {
+                first: 10,
+                increment: 1,
+                ..Reduce::default()
+            },
+            ..Config::default()
+        };
+        let (model, statistics) = solve_with_config(pigeonhole(5), config);
+        assert!(model.is_none());
+        assert!(statistics.deleted_clauses > 0);
+    }
+
+    #[test]
+    fn test_limits() {
+        let mut solver = Solver::from_cnf(&pigeonhole(6), Config::default());
+        solver.set_limits(Limits {
+            conflicts: Some(10),
+            ..Limits::default()
+        });
+        assert_eq!(solver.solve(), Status::Unknown(Interrupt::Conflicts));
+        assert_eq!(solver.statistics().conflicts, 10);
+        assert!(solver.model().is_none());
+        // limits apply to each call, while statistics add up over the calls
+        assert_eq!(solver.solve(), Status::Unknown(Interrupt::Conflicts));
+        assert_eq!(solver.statistics().conflicts, 20);
+
+        solver.set_limits(Limits {
+            decisions: Some(5),
+            ..Limits::default()
+        });
+        assert_eq!(solver.solve(), Status::Unknown(Interrupt::Decisions));
+
+        solver.set_limits(Limits::default());
+        assert_eq!(solver.solve(), Status::Unsatisfiable);
+    }
+
+    #[test]
+    fn test_terminate() {
+        let mut solver = Solver::from_cnf(&pigeonhole(6), Config::default());
+        let flag = Arc::new(AtomicBool::new(true));
+        solver.set_terminate(Some(Terminate::flag(Arc::clone(&flag))));
+        assert_eq!(solver.solve(), Status::Unknown(Interrupt::Cancelled));
+
+        let polls = AtomicUsize::new(0);
+        solver.set_terminate(Some(Terminate::callback(move || {
+            polls.fetch_add(1, Ordering::Relaxed) >= 100
+        })));
+        assert_eq!(solver.solve(), Status::Unknown(Interrupt::Cancelled));
+        assert!(solver.statistics().conflicts > 0);
+
+        flag.store(false, Ordering::Relaxed);
+        solver.set_terminate(Some(flag.into()));
+        assert_eq!(solver.solve(), Status::Unsatisfiable);
+    }
+
+    #[test]
+    fn test_failed_assumptions() {
+        let mut names = NameTable::new();
+        let mut solver = Solver::new(Config::default());
+        solver.add_clause(&names.parse_clause("!A \\/ B").unwrap());
+        solver.add_clause(&names.parse_clause("!B \\/ C").unwrap());
+
+        let assumptions: Vec<Literal> = vec!["A", "D", "!C"]
+            .into_iter()
+            .map(|l| names.parse_literal(l).unwrap())
+            .collect();
+        assert_eq!(
+            solver.solve_with_assumptions(&assumptions),
+            Status::Unsatisfiable
+        );
+
+        let mut failed: Vec<_> = solver
+            .failed_assumptions()
+            .iter()
+            .map(|l| names.display(l).to_string())
+            .collect();
+        failed.sort();
+        assert_eq!(failed, vec!["A", "¬C"]);
+    }
+
+    #[test]
+    fn test_incremental() {
+        let mut names = NameTable::new();
+        let mut clause = |s| names.parse_clause(s).unwrap();
+        let mut solver = Solver::new(Config::default());
+        solver.add_clause(&clause("A \\/ B"));
+        solver.add_clause(&clause("!A \\/ C"));
+        assert_eq!(solver.solve(), Status::Satisfiable);
+
+        let not_b = *clause("!B").unit().unwrap();
+        let not_c = *clause("!C").unit().unwrap();
+        assert_eq!(solver.solve_with_assumptions(&[not_b]), Status::Satisfiable);
+        assert_eq!(solver.value(&not_c.variable()), Some(Truth::True));
+        assert_eq!(
+            solver.solve_with_assumptions(&[not_b, not_c]),
+            Status::Unsatisfiable
+        );
+        // assumptions do not persist
+        assert_eq!(solver.solve(), Status::Satisfiable);
+
+        solver.add_clause(&clause("!B"));
+        solver.add_clause(&clause("!C"));
+        assert_eq!(solver.solve(), Status::Unsatisfiable);
+        assert!(solver.model().is_none());
+    }
+
+    #[test]
+    fn test_add_cnf_names() {
+        let mut cnf: CNF = "A ∨ B".parse().unwrap();
+        let mut solver = Solver::from_cnf(&cnf, Config::default());
+        let clause = cnf.names_mut().parse_clause("¬A ∨ C").unwrap();
+        cnf.add_clause(clause);
+        solver.add_cnf(&cnf).unwrap();
+
+        // `B` is the first variable of the new CNF, which is `A` in the solver
+        let separate: CNF = "¬B".parse().unwrap();
+        assert!(solver.add_cnf(&separate).is_err());
+        // the clause of the rejected CNF is not added as `¬A`
+        let a = *cnf.names_mut().parse_clause("A").unwrap().unit().unwrap();
+        assert_eq!(solver.solve_with_assumptions(&[a]), Status::Satisfiable);
+    }
+
+    #[test]
+    fn test_proof() {
+        let input = "p cnf 2 4\n1 2 0\n-1 2 0\n1 -2 0\n-1 -2 0\n";
+        let cnf = dimacs::parse(input.as_bytes()).unwrap();
+        let buffer = SharedBuffer::default();
+        let mut solver = Solver::new(Config::default());
+        solver.set_proof(DratWriter::new(Box::new(buffer.clone()), Encoding::Text));
+        solver.add_cnf(&cnf).unwrap();
+        assert_eq!(solver.solve(), Status::Unsatisfiable);
+        solver.finish_proof().unwrap();
+        let proof = proof::parse_drat(&buffer.0.borrow()[..]).unwrap();
+        assert!(proof::check_drat(&cnf, &proof).is_ok());
+
+        // variables without DIMACS numbers cannot be written
+        let mut solver = Solver::from_cnf(&pigeonhole(2), Config::default());
+        solver.set_proof(DratWriter::new(Box::new(io::sink()), Encoding::Text));
+        assert_eq!(solver.solve(), Status::Unsatisfiable);
+        assert!(solver.finish_proof().is_err());
+    }
+
+    #[test]
+    fn test_lrat_proof() {
+        let cnf = dimacs::parse(include_bytes!("../../benches/data/hole6.cnf").as_ref()).unwrap();
+        let buffer = SharedBuffer::default();
+        let mut solver = Solver::new(Config::default());
+        solver.set_lrat_proof(LratWriter::new(Box::new(buffer.clone())));
+        solver.add_cnf(&cnf).unwrap();
+        assert_eq!(solver.solve(), Status::Unsatisfiable);
+        solver.finish_proof().unwrap();
+        let proof = proof::parse_lrat(&buffer.0.borrow()[..]).unwrap();
+        assert!(proof::check_lrat(&cnf, &proof).is_ok());
+    }
 }
@@ -0,0 +1,137 @@
+use std::ops;
+
+use super::lit::Lit;
+use crate::cnf::{ClauseArena, ClauseID, Relocation};
+
+pub use crate::cnf::ClauseRef;
+
+/// Storage of the clauses watched by the solver.
+///
+/// Clauses live in a contiguous arena. Learned clauses additionally carry
+/// their LBD (glue) and an activity that is bumped whenever they take part in
+/// conflict analysis. Removed clauses are reclaimed by `collect_garbage` once
+/// they take up a large enough part of the arena.
+pub struct ClauseDB {
+    arena: ClauseArena<Lit>,
+    learned: Vec<ClauseRef>,
+    increment: f32,
+    decay: f32,
+}
+
+impl ops::Index<ClauseRef> for ClauseDB {
+    type Output = [Lit];
+    fn index(&self, cref: ClauseRef) -> &[Lit] {
+        &self.arena[cref]
+    }
+}
+
+impl ops::IndexMut<ClauseRef> for ClauseDB {
+    fn index_mut(&mut self, cref: ClauseRef) -> &mut [Lit] {
+        &mut self.arena[cref]
+    }
+}
+
+impl ClauseDB {
+    pub fn new(decay: f32) -> Self {
+        ClauseDB {
+            arena: ClauseArena::new(),
+            learned: Vec::new(),
+            increment: 1.0,
+            decay,
+        }
+    }
+
+    pub fn add(&mut self, id: ClauseID, literals: Vec<Lit>) -> ClauseRef {
+        debug_assert!(literals.len() >= 2);
+        self.arena.alloc(id, &literals, false)
+    }
+
+    pub fn add_learned(&mut self, id: ClauseID, literals: Vec<Lit>, lbd: usize) -> ClauseRef {
+        debug_assert!(literals.len() >= 2);
+        let cref = self.arena.alloc(id, &literals, true);
+        self.arena.set_lbd(cref, lbd);
+        self.learned.push(cref);
+        self.bump(cref);
+        cref
+    }
+
+    /// Remove a learned clause. It must not be watched or be a reason anymore.
+    pub fn remove(&mut self, cref: ClauseRef) {
+        debug_assert!(self.arena.is_learned(cref));
+        self.arena.remove(cref);
+    }
+
+    pub fn learned(&self) -> &[ClauseRef] {
+        &self.learned
+    }
+
+    pub fn retain_learned<F>(&mut self, f: F)
+    where
+        F: FnMut(&ClauseRef) -> bool,
+    {
+        self.learned.retain(f);
+    }
+
+    pub fn id(&self, cref: ClauseRef) -> ClauseID {
+        self.arena.id(cref)
+    }
+
+    pub fn is_learned(&self, cref: ClauseRef) -> bool {
+        self.arena.is_learned(cref)
+    }
+
+    pub fn lbd(&self, cref: ClauseRef) -> usize {
+        self.arena.lbd(cref)
+    }
+
+    pub fn activity(&self, cref: ClauseRef) -> f32 {
+        self.arena.activity(cref)
+    }
+
+    /// Record a use of the clause in conflict analysis, with its current LBD.
+    pub fn mark_used(&mut self, cref: ClauseRef, lbd: usize) {
+        self.arena.set_used(cref, true);
+        if lbd < self.arena.lbd(cref) {
+            self.arena.set_lbd(cref, lbd);
+        }
+    }
+
+    /// Whether the clause was used since the last call, resetting the flag.
+    pub fn take_used(&mut self, cref: ClauseRef) -> bool {
+        let used = self.arena.is_used(cref);
+        self.arena.set_used(cref, false);
+        used
+    }
+
+    pub fn bump(&mut self, cref: ClauseRef) {
+        let activity = self.arena.activity(cref) + self.increment;
+        self.arena.set_activity(cref, activity);
+        if activity > 1e20 {
+            for &cref in &self.learned {
+                let activity = self.arena.activity(cref);
+                self.arena.set_activity(cref, activity * 1e-20);
+            }
+            self.increment *= 1e-20;
+        }
+    }
+
+    pub fn decay(&mut self) {
+        self.increment /= self.decay;
+    }
+
+    /// Whether removed clauses take up enough of the arena to collect them.
+    pub fn needs_collection(&self) -> bool {
+        self.arena.wasted() as f64 > self.arena.size() as f64 * GARBAGE_FRACTION
+    }
+
+    /// Compact the arena. Clause references held outside must be relocated.
+    pub fn collect_garbage(&mut self) -> Relocation {
+        let relocation = self.arena.collect_garbage();
+        for cref in &mut self.learned {
+            *cref = relocation.get(*cref).unwrap();
+        }
+        relocation
+    }
+}
+
+const GARBAGE_FRACTION: f64 = 0.2;
@@ -0,0 +1,272 @@
+use std::{error, fmt, str};
+
+/// How the polarity of a decision variable is chosen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    /// Always assign `False`.
+    False,
+    /// Always assign `True`.
+    True,
+    /// Reuse the value the variable had when it was last unassigned.
+    Saved,
+    /// Pick a polarity uniformly at random, from `Config::seed`.
+    Random,
+    /// Reuse the value from the largest conflict-free assignment seen so far.
+    Target,
+}
+
+/// When the search is restarted from the initial level, keeping learned clauses.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Restart {
+    Never,
+    /// Restart after `unit` times the next element of the Luby sequence conflicts.
+    Luby {
+        unit: u64,
+    },
+    /// Restart after `initial` conflicts, multiplying the interval by `factor` each time.
+    Geometric {
+        initial: u64,
+        factor: f64,
+    },
+    /// Restart when the recent average LBD of learned clauses exceeds the
+    /// long-term average by `margin`, as done in Glucose.
+    Glucose {
+        fast_decay: f64,
+        slow_decay: f64,
+        margin: f64,
+        min_conflicts: u64,
+    },
+}
+
+impl Restart {
+    pub fn luby() -> Restart {
+        Restart::Luby { unit: 100 }
+    }
+
+    pub fn geometric() -> Restart {
+        Restart::Geometric {
+            initial: 100,
+            factor: 1.5,
+        }
+    }
+
+    /// Check that the parameters let the search progress between restarts.
+    pub fn validate(&self) -> Result<(), ParseRestartError> {
+        let out_of_range = |parameter, expected| {
+            Err(ParseRestartError::OutOfRange {
+                parameter,
+                expected,
+            })
+        };
+        match *self {
+            Restart::Never => Ok(()),
+            Restart::Luby { unit } if unit < 1 => out_of_range("unit", "at least 1"),
+            Restart::Luby { .. } => Ok(()),
+            Restart::Geometric { initial, .. } if initial < 1 => {
+                out_of_range("initial interval", "at least 1")
+            }
+            Restart::Geometric { factor, .. } if !(factor > 1.0 && factor.is_finite()) => {
+                out_of_range("factor", "greater than 1")
+            }
+            Restart::Geometric { .. } => Ok(()),
+            Restart::Glucose {
+                fast_decay,
+                slow_decay,
+                ..
+            } if !(fast_decay > 0.0
+                && fast_decay <= 1.0
+                && slow_decay > 0.0
+                && slow_decay <= 1.0) =>
+            {
+                out_of_range("decay", "in (0, 1]")
+            }
+            Restart::Glucose { margin, .. } if !(margin > 0.0 && margin.is_finite()) => {
+                out_of_range("margin", "positive")
+            }
+            Restart::Glucose { min_conflicts, .. } if min_conflicts < 1 => {
+                out_of_range("min conflicts", "at least 1")
+            }
+            Restart::Glucose { .. } => Ok(()),
+        }
+    }
+
+    pub fn glucose() -> Restart {
+        Restart::Glucose {
+            fast_decay: 1.0 / 32.0,
+            slow_decay: 1.0 / 4096.0,
+            margin: 1.25,
+            min_conflicts: 50,
+        }
+    }
+}
+
+/// When and which learned clauses are dropped from the clause database.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Reduce {
+    /// Conflicts before the first reduction.
+    pub first: u64,
+    /// Growth of the interval between reductions, in conflicts.
+    pub increment: u64,
+    /// Clauses with at most this LBD are kept forever.
+    pub core_lbd: usize,
+    /// Clauses with at most this LBD are kept while they keep being used.
+    pub tier2_lbd: usize,
+    /// Fraction of the remaining clauses removed, least active first.
+    pub fraction: f64,
+}
+
+impl Default for Reduce {
+    fn default() -> Reduce {
+        Reduce {
+            first: 2000,
+            increment: 300,
+            core_lbd: 2,
+            tier2_lbd: 6,
+            fraction: 0.5,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    pub phase: Phase,
+    pub seed: u64,
+    pub restart: Restart,
+    pub reduce: Reduce,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            phase: Phase::Saved,
+            seed: 0,
+            restart: Restart::luby(),
+            reduce: Reduce::default(),
+        }
+    }
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Phase::False => f.pad("false"),
+            Phase::True => f.pad("true"),
+            Phase::Saved => f.pad("saved"),
+            Phase::Random => f.pad("random"),
+            Phase::Target => f.pad("target"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ParsePhaseError(String);
+
+impl fmt::Display for ParsePhaseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown phase: {}", self.0)
+    }
+}
+
+impl error::Error for ParsePhaseError {}
+
+impl str::FromStr for Phase {
+    type Err = ParsePhaseError;
+    fn from_str(s: &str) -> Result<Phase, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "false" => Ok(Phase::False),
+            "true" => Ok(Phase::True),
+            "saved" => Ok(Phase::Saved),
+            "random" => Ok(Phase::Random),
+            "target" => Ok(Phase::Target),
+            _ => Err(ParsePhaseError(s.to_owned())),
+        }
+    }
+}
+
+/// Written as the name of the schedule followed by its parameters, such as
+/// `geometric:100:1.5`.
+impl fmt::Display for Restart {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Restart::Never => write!(f, "never"),
+            Restart::Luby { unit } => write!(f, "luby:{}", unit),
+            Restart::Geometric { initial, factor } => {
+                write!(f, "geometric:{}:{}", initial, factor)
+            }
+            Restart::Glucose {
+                fast_decay,
+                slow_decay,
+                margin,
+                min_conflicts,
+            } => write!(
+                f,
+                "glucose:{}:{}:{}:{}",
+                fast_decay, slow_decay, margin, min_conflicts
+            ),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ParseRestartError {
+    Unknown(String),
+    OutOfRange {
+        parameter: &'static str,
+        expected: &'static str,
+    },
+}
+
+impl fmt::Display for ParseRestartError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseRestartError::Unknown(s) => write!(f, "unknown restart schedule: {}", s),
+            ParseRestartError::OutOfRange {
+                parameter,
+                expected,
+            } => write!(f, "restart {} must be {}", parameter, expected),
+        }
+    }
+}
+
+impl error::Error for ParseRestartError {}
+
+impl str::FromStr for Restart {
+    type Err = ParseRestartError;
+    fn from_str(s: &str) -> Result<Restart, Self::Err> {
+        fn param<T: str::FromStr>(p: &str) -> Option<T> {
+            p.trim().parse().ok()
+        }
+
+        let parts: Vec<_> = s.split(':').collect();
+        let restart = match (parts[0].to_lowercase().as_str(), &parts[1..]) {
+            ("never", []) => Some(Restart::Never),
+            ("luby", []) => Some(Restart::luby()),
+            ("luby", [unit]) => param(unit).map(|unit| Restart::Luby { unit }),
+            ("geometric", []) => Some(Restart::geometric()),
+            ("geometric", [initial, factor]) => param(initial)
+                .zip(param(factor))
+                .map(|(initial, factor)| Restart::Geometric { initial, factor }),
+            ("glucose", []) => Some(Restart::glucose()),
+            ("glucose", [fast_decay, slow_decay, margin, min_conflicts]) => match (
+                param(fast_decay),
+                param(slow_decay),
+                param(margin),
+                param(min_conflicts),
+            ) {
+                (Some(fast_decay), Some(slow_decay), Some(margin), Some(min_conflicts)) => {
+                    Some(Restart::Glucose {
+                        fast_decay,
+                        slow_decay,
+                        margin,
+                        min_conflicts,
+                    })
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+        let restart = restart.ok_or_else(|| ParseRestartError::Unknown(s.to_owned()))?;
+        restart.validate()?;
+        Ok(restart)
+    }
+}
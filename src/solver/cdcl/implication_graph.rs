@@ -1,130 +1,130 @@
-use std::fmt;
-
+use super::clause_db::ClauseRef;
 use super::level::Level;
+use super::lit::{Lit, Var};
 use crate::assignment::Truth;
-use crate::cnf::Variable;
+use crate::cnf::Relocation;
 
 use log::debug;
-use petgraph::graph::{DiGraph, NodeIndex};
-use petgraph::visit::IntoNodeReferences;
-use petgraph::Direction;
 
+#[derive(Clone, Copy)]
 struct Node {
-    variable: Variable,
     truth: Truth,
     level: Level,
+    reason: Option<ClauseRef>,
 }
 
-impl fmt::Display for Node {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{} := {} @{}", self.variable, self.truth, self.level)
-    }
+/// The implication graph, kept as an assignment trail.
+///
+/// Each assigned variable remembers its level and the clause that implied it;
+/// the edges of the graph are the other literals of that reason clause.
+/// Assignments of each level are contiguous on the trail, so erasing levels
+/// is a truncation.
+pub struct ImplicationGraph {
+    trail: Vec<Lit>,
+    level_starts: Vec<usize>,
+    nodes: Vec<Option<Node>>,
 }
 
-struct Edge;
-
-impl fmt::Display for Edge {
-    fn fmt(&self, _f: &mut fmt::Formatter) -> fmt::Result {
-        Ok(())
+impl ImplicationGraph {
+    pub fn new() -> Self {
+        ImplicationGraph {
+            trail: Vec::new(),
+            level_starts: Vec::new(),
+            nodes: Vec::new(),
+        }
     }
-}
 
-pub struct ImplicationGraph {
-    graph: DiGraph<Node, Edge>,
-}
-
-#[derive(PartialEq, Eq, Hash, Clone)]
-pub struct Decision {
-    idx: NodeIndex,
-    variable: Variable,
-    truth: Truth,
-    level: Level,
-}
+    pub fn grow(&mut self, num_vars: usize) {
+        self.nodes.resize(num_vars, None);
+    }
 
-impl Decision {
     pub fn level(&self) -> Level {
-        self.level
+        Level::from_index(self.level_starts.len())
     }
 
-    pub fn variable(&self) -> &Variable {
-        &self.variable
+    pub fn trail(&self) -> &[Lit] {
+        &self.trail
     }
 
-    pub fn truth(&self) -> Truth {
-        self.truth
+    pub fn new_level(&mut self) -> Level {
+        self.level_starts.push(self.trail.len());
+        self.level()
     }
-}
 
-impl ImplicationGraph {
-    pub fn new() -> Self {
-        ImplicationGraph {
-            graph: DiGraph::new(),
+    /// Assignments made below level `at`.
+    pub fn assigned_below(&self, at: Level) -> &[Lit] {
+        if at > self.level() {
+            return &self.trail;
+        }
+        if at == Level::initial() {
+            return &[];
         }
+        &self.trail[..self.level_starts[at.index() - 1]]
     }
 
+    /// Assignments made at level `at` and all levels above it.
+    pub fn assigned_since(&self, at: Level) -> &[Lit] {
+        if at > self.level() || at == Level::initial() {
+            return &[];
+        }
+        &self.trail[self.level_starts[at.index() - 1]..]
+    }
+
+    /// Erase the assignments at level `at` and all levels above it.
     pub fn erase(&mut self, at: Level) {
         debug!("ERASE: {:?}", at);
-        self.graph
-            .retain_nodes(|g, idx| g.node_weight(idx).unwrap().level != at)
+        if at > self.level() {
+            return;
+        }
+        if at == Level::initial() {
+            panic!("attempt to erase the initial level");
+        }
+
+        let start = self.level_starts[at.index() - 1];
+        for lit in self.trail.drain(start..) {
+            self.nodes[lit.var().index()] = None;
+        }
+        self.level_starts.truncate(at.index() - 1);
     }
 
-    pub fn find_decision(&self, variable: &Variable, truth: Truth) -> Option<Decision> {
-        self.graph
-            .node_references()
-            .find(|(_, n)| &n.variable == variable && n.truth == truth)
-            .map(|(idx, _)| self.get_decision(idx))
+    pub fn value(&self, variable: Var) -> Option<Truth> {
+        self.nodes[variable.index()].map(|n| n.truth)
     }
 
-    // TODO: Refactor API
-    pub fn predecessors(&self, decision: &Decision) -> Vec<Decision> {
-        self.graph
-            .neighbors_directed(decision.idx, Direction::Incoming)
-            .map(|i| self.get_decision(i))
-            .collect()
+    pub fn literal_value(&self, lit: Lit) -> Option<bool> {
+        self.value(lit.var())
+            .and_then(Truth::as_bool)
+            .map(|value| value != lit.is_negated())
     }
 
-    fn get_decision(&self, idx: NodeIndex) -> Decision {
-        let Node {
-            variable,
-            truth,
-            level,
-        } = self.graph.node_weight(idx).unwrap();
-        Decision {
-            idx,
-            variable: variable.clone(),
-            truth: *truth,
-            level: *level,
+    /// Follow the reasons moved by garbage collection.
+    pub fn relocate(&mut self, relocation: &Relocation) {
+        for lit in &self.trail {
+            if let Some(node) = &mut self.nodes[lit.var().index()] {
+                node.reason = node.reason.map(|cref| relocation.get(cref).unwrap());
+            }
         }
     }
 
-    pub fn make_decision<I>(
-        &mut self,
-        variable: &Variable,
-        truth: Truth,
-        level: Level,
-        implicants: I,
-    ) -> Decision
-    where
-        I: IntoIterator<Item = Decision>,
-    {
-        let node_data = Node {
-            variable: variable.clone(),
-            truth,
-            level,
-        };
-        debug!("make_decision: {}", node_data);
+    pub fn reason(&self, variable: Var) -> Option<ClauseRef> {
+        self.nodes[variable.index()].and_then(|n| n.reason)
+    }
 
-        let idx = self.graph.add_node(node_data);
+    pub fn level_of(&self, variable: Var) -> Option<Level> {
+        self.nodes[variable.index()].map(|n| n.level)
+    }
 
-        for implicant in implicants.into_iter() {
-            self.graph.add_edge(implicant.idx, idx, Edge);
-        }
+    pub fn make_decision(&mut self, variable: Var, truth: Truth, reason: Option<ClauseRef>) {
+        let level = self.level();
+        debug!("make_decision: {} := {} @{}", variable, truth, level);
 
-        Decision {
-            idx,
-            variable: variable.clone(),
+        debug_assert!(self.nodes[variable.index()].is_none());
+        debug_assert!(truth.is_defined());
+        self.nodes[variable.index()] = Some(Node {
             truth,
             level,
-        }
+            reason,
+        });
+        self.trail.push(Lit::new(variable, truth == Truth::False));
     }
 }
@@ -8,6 +8,14 @@ impl Level {
         Level(0)
     }
 
+    pub fn from_index(index: usize) -> Level {
+        Level(index)
+    }
+
+    pub fn index(self) -> usize {
+        self.0
+    }
+
     pub fn next(self) -> Level {
         Level(self.0 + 1)
     }
@@ -0,0 +1,128 @@
+use std::{fmt, ops};
+
+use crate::cnf::{Literal, Variable, Word};
+use crate::solver::watch::WatchedLiteral;
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Hash)]
+pub struct Var(u32);
+
+impl Var {
+    pub fn from_index(index: usize) -> Var {
+        Var(index as u32)
+    }
+
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl fmt::Display for Var {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// Literal encoded as `2v` (positive) or `2v + 1` (negative).
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Hash)]
+pub struct Lit(u32);
+
+impl Lit {
+    pub fn new(var: Var, is_negated: bool) -> Lit {
+        Lit(var.0 << 1 | is_negated as u32)
+    }
+
+    pub fn var(self) -> Var {
+        Var(self.0 >> 1)
+    }
+
+    pub fn is_negated(self) -> bool {
+        self.0 & 1 == 1
+    }
+
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl Word for Lit {
+    fn from_word(word: u32) -> Lit {
+        Lit(word)
+    }
+
+    fn word(self) -> u32 {
+        self.0
+    }
+}
+
+impl WatchedLiteral for Lit {
+    fn index(self) -> usize {
+        Lit::index(self)
+    }
+}
+
+impl ops::Not for Lit {
+    type Output = Lit;
+    fn not(self) -> Lit {
+        Lit(self.0 ^ 1)
+    }
+}
+
+impl fmt::Display for Lit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_negated() {
+            write!(f, "¬{}", self.var())
+        } else {
+            write!(f, "{}", self.var())
+        }
+    }
+}
+
+/// Bidirectional mapping between `Variable`s and dense solver indices.
+pub struct VarTable {
+    variables: Vec<Variable>,
+    /// Solver indices indexed by `Variable::index`.
+    indices: Vec<Option<Var>>,
+}
+
+impl VarTable {
+    pub fn new() -> Self {
+        VarTable {
+            variables: Vec::new(),
+            indices: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.variables.len()
+    }
+
+    pub fn intern(&mut self, variable: &Variable) -> Var {
+        if let Some(Some(var)) = self.indices.get(variable.index()) {
+            return *var;
+        }
+
+        let var = Var::from_index(self.variables.len());
+        self.variables.push(*variable);
+        if variable.index() >= self.indices.len() {
+            self.indices.resize(variable.index() + 1, None);
+        }
+        self.indices[variable.index()] = Some(var);
+        var
+    }
+
+    pub fn intern_literal(&mut self, literal: &Literal) -> Lit {
+        Lit::new(self.intern(&literal.variable()), literal.is_negated())
+    }
+
+    pub fn variable(&self, var: Var) -> &Variable {
+        &self.variables[var.index()]
+    }
+
+    pub fn literal(&self, lit: Lit) -> Literal {
+        Literal::new(*self.variable(lit.var()), lit.is_negated())
+    }
+
+    pub fn vars(&self) -> impl Iterator<Item = Var> {
+        (0..self.variables.len()).map(Var::from_index)
+    }
+}
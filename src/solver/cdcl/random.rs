@@ -0,0 +1,49 @@
+/// Small xorshift64* generator, so that random choices are reproducible from a seed.
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        // xorshift stays at zero once there, so a zero state is replaced
+        let state = splitmix64(seed);
+        Random {
+            state: if state == 0 { GOLDEN_GAMMA } else { state },
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    pub fn next_bool(&mut self) -> bool {
+        self.next_u64() >> 63 == 1
+    }
+}
+
+const GOLDEN_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// One step of splitmix64, spreading the bits of similar seeds.
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(GOLDEN_GAMMA);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Random, GOLDEN_GAMMA};
+
+    #[test]
+    fn test_seeds() {
+        for &seed in [0, 1, GOLDEN_GAMMA, u64::MAX].iter() {
+            let mut random = Random::new(seed);
+            let trues = (0..1000).filter(|_| random.next_bool()).count();
+            assert!(trues > 300 && trues < 700, "seed {}", seed);
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use super::config::Restart;
+
+/// Decides when to restart the search, following the configured schedule.
+pub struct Restarter {
+    policy: Restart,
+    conflicts: u64,
+    limit: u64,
+    restarts: u32,
+    fast: Ema,
+    slow: Ema,
+}
+
+impl Restarter {
+    pub fn new(policy: Restart) -> Self {
+        let (fast, slow) = match policy {
+            Restart::Glucose {
+                fast_decay,
+                slow_decay,
+                ..
+            } => (Ema::new(fast_decay), Ema::new(slow_decay)),
+            _ => (Ema::new(1.0), Ema::new(1.0)),
+        };
+        let mut restarter = Restarter {
+            policy,
+            conflicts: 0,
+            limit: 0,
+            restarts: 0,
+            fast,
+            slow,
+        };
+        restarter.limit = restarter.next_limit();
+        restarter
+    }
+
+    pub fn on_conflict(&mut self, lbd: usize) {
+        self.conflicts += 1;
+        self.fast.update(lbd as f64);
+        self.slow.update(lbd as f64);
+    }
+
+    pub fn should_restart(&self) -> bool {
+        match self.policy {
+            Restart::Never => false,
+            Restart::Luby { .. } | Restart::Geometric { .. } => self.conflicts >= self.limit,
+            Restart::Glucose {
+                margin,
+                min_conflicts,
+                ..
+            } => self.conflicts >= min_conflicts && self.fast.value() > margin * self.slow.value(),
+        }
+    }
+
+    pub fn on_restart(&mut self) {
+        self.conflicts = 0;
+        self.restarts += 1;
+        self.limit = self.next_limit();
+    }
+
+    /// At least one conflict, so that the search always progresses between restarts.
+    fn next_limit(&self) -> u64 {
+        let limit = match self.policy {
+            Restart::Luby { unit } => unit.saturating_mul(luby(self.restarts)),
+            Restart::Geometric { initial, factor } => {
+                (initial as f64 * factor.powi(self.restarts as i32)) as u64
+            }
+            Restart::Never | Restart::Glucose { .. } => u64::MAX,
+        };
+        limit.max(1)
+    }
+}
+
+/// Exponential moving average, corrected for the bias towards its initial
+/// value of zero as in CaDiCaL.
+struct Ema {
+    biased: f64,
+    decay: f64,
+    /// Weight left on the initial value, `(1 - decay)^n` after `n` updates.
+    remaining: f64,
+}
+
+impl Ema {
+    fn new(decay: f64) -> Self {
+        Ema {
+            biased: 0.0,
+            decay,
+            remaining: 1.0,
+        }
+    }
+
+    fn update(&mut self, x: f64) {
+        self.biased += self.decay * (x - self.biased);
+        self.remaining *= 1.0 - self.decay;
+    }
+
+    fn value(&self) -> f64 {
+        if self.remaining >= 1.0 {
+            0.0
+        } else {
+            self.biased / (1.0 - self.remaining)
+        }
+    }
+}
+
+/// The `index`-th element (0-based) of the Luby sequence 1, 1, 2, 1, 1, 2, 4, 1, ...
+fn luby(index: u32) -> u64 {
+    let mut x = u64::from(index);
+    let mut size = 1;
+    let mut seq = 0;
+    while size < x + 1 {
+        seq += 1;
+        size = 2 * size + 1;
+    }
+    while size - 1 != x {
+        size = (size - 1) >> 1;
+        seq -= 1;
+        x %= size;
+    }
+    1 << seq
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{luby, Ema, Restart, Restarter};
+
+    #[test]
+    fn test_luby() {
+        let seq: Vec<_> = (0..15).map(luby).collect();
+        assert_eq!(seq, vec![1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8]);
+    }
+
+    #[test]
+    fn test_ema() {
+        let mut ema = Ema::new(1.0 / 4096.0);
+        ema.update(5.0);
+        assert!((ema.value() - 5.0).abs() < 1e-9);
+        ema.update(7.0);
+        assert!(ema.value() > 5.0 && ema.value() < 7.0);
+    }
+
+    #[test]
+    fn test_restart_limits() {
+        for policy in [
+            "luby:0",
+            "geometric:0:2",
+            "geometric:10:1",
+            "geometric:10:0.5",
+            "glucose:0.5:0.001:0.1:0",
+        ]
+        .iter()
+        {
+            assert!(policy.parse::<Restart>().is_err(), "{}", policy);
+        }
+
+        // steady LBDs never look worse than the long-term average
+        let mut restarter = Restarter::new(Restart::glucose());
+        for _ in 0..1000 {
+            restarter.on_conflict(5);
+        }
+        assert!(!restarter.should_restart());
+    }
+}
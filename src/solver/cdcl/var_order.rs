@@ -0,0 +1,126 @@
+use super::lit::Var;
+
+/// Variable activities for VSIDS branching, with a binary max-heap of
+/// candidate variables ordered by activity.
+///
+/// Activities are bumped by an increment that grows geometrically after each
+/// conflict (EVSIDS), which is equivalent to decaying all other activities.
+pub struct VarOrder {
+    activity: Vec<f64>,
+    increment: f64,
+    decay: f64,
+    heap: Vec<Var>,
+    positions: Vec<Option<usize>>,
+}
+
+impl VarOrder {
+    pub fn new(decay: f64) -> Self {
+        VarOrder {
+            activity: Vec::new(),
+            increment: 1.0,
+            decay,
+            heap: Vec::new(),
+            positions: Vec::new(),
+        }
+    }
+
+    pub fn grow(&mut self, num_vars: usize) {
+        let old = self.activity.len();
+        self.activity.resize(num_vars, 0.0);
+        self.positions.resize(num_vars, None);
+        for index in old..num_vars {
+            self.insert(Var::from_index(index));
+        }
+    }
+
+    pub fn bump(&mut self, var: Var) {
+        self.activity[var.index()] += self.increment;
+        if self.activity[var.index()] > 1e100 {
+            for a in &mut self.activity {
+                *a *= 1e-100;
+            }
+            self.increment *= 1e-100;
+        }
+
+        if let Some(pos) = self.positions[var.index()] {
+            self.sift_up(pos);
+        }
+    }
+
+    pub fn decay(&mut self) {
+        self.increment /= self.decay;
+    }
+
+    pub fn contains(&self, var: Var) -> bool {
+        self.positions[var.index()].is_some()
+    }
+
+    pub fn insert(&mut self, var: Var) {
+        if self.contains(var) {
+            return;
+        }
+
+        let pos = self.heap.len();
+        self.heap.push(var);
+        self.positions[var.index()] = Some(pos);
+        self.sift_up(pos);
+    }
+
+    /// Remove and return the variable with the highest activity.
+    pub fn pop(&mut self) -> Option<Var> {
+        if self.heap.is_empty() {
+            return None;
+        }
+
+        let top = self.heap.swap_remove(0);
+        self.positions[top.index()] = None;
+        if !self.heap.is_empty() {
+            self.positions[self.heap[0].index()] = Some(0);
+            self.sift_down(0);
+        }
+        Some(top)
+    }
+
+    fn less(&self, a: Var, b: Var) -> bool {
+        let (x, y) = (self.activity[a.index()], self.activity[b.index()]);
+        x < y || (x == y && a > b)
+    }
+
+    fn swap(&mut self, i: usize, j: usize) {
+        self.heap.swap(i, j);
+        self.positions[self.heap[i].index()] = Some(i);
+        self.positions[self.heap[j].index()] = Some(j);
+    }
+
+    fn sift_up(&mut self, mut pos: usize) {
+        while pos > 0 {
+            let parent = (pos - 1) / 2;
+            if !self.less(self.heap[parent], self.heap[pos]) {
+                break;
+            }
+            self.swap(parent, pos);
+            pos = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut pos: usize) {
+        loop {
+            let left = 2 * pos + 1;
+            if left >= self.heap.len() {
+                break;
+            }
+
+            let right = left + 1;
+            let child = if right < self.heap.len() && self.less(self.heap[left], self.heap[right]) {
+                right
+            } else {
+                left
+            };
+            if !self.less(self.heap[pos], self.heap[child]) {
+                break;
+            }
+            self.swap(pos, child);
+            pos = child;
+        }
+    }
+}
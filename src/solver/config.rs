@@ -0,0 +1,439 @@
+use std::time::Duration;
+use std::{error, fmt, str};
+
+use super::cdcl::{self, ParseRestartError, Phase, Reduce, Restart};
+use super::dpll::{self, Heuristic};
+
+/// Budgets of a single run; `None` means unlimited.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Limits {
+    pub conflicts: Option<u64>,
+    pub propagations: Option<u64>,
+    pub decisions: Option<u64>,
+    pub time: Option<Duration>,
+}
+
+/// Configuration of the solvers.
+///
+/// Each parameter has a key, so that it can be set from text with `set`.
+/// `Display` writes every parameter as comma-separated `key=value` pairs,
+/// which `set_all` reads back to reproduce a run.
+/// `to_pairs` writes those of some sections only.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Config {
+    pub dpll: dpll::Config,
+    pub cdcl: cdcl::Config,
+    pub limits: Limits,
+}
+
+/// Which solvers a parameter applies to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Section {
+    DPLL,
+    CDCL,
+    Limits,
+}
+
+/// Keys of the parameters, in the order they are displayed, with the values
+/// they accept.
+const KEYS: &[(&str, Section, &str)] = &[
+    (
+        "heuristic",
+        Section::DPLL,
+        "one of MOMS, JW, JW2, DLIS, DLCS and Bohm",
+    ),
+    (
+        "phase",
+        Section::CDCL,
+        "one of false, true, saved, random and target",
+    ),
+    ("seed", Section::CDCL, "a non-negative integer"),
+    (
+        "restarts",
+        Section::CDCL,
+        "never, luby[:UNIT], geometric[:INITIAL:FACTOR] or glucose[:FAST:SLOW:MARGIN:MIN], \
+         with UNIT, INITIAL and MIN at least 1",
+    ),
+    ("reduce-first", Section::CDCL, "a non-negative integer"),
+    ("reduce-increment", Section::CDCL, "a non-negative integer"),
+    (
+        "core-lbd",
+        Section::CDCL,
+        "a non-negative integer, at most tier2-lbd",
+    ),
+    (
+        "tier2-lbd",
+        Section::CDCL,
+        "a non-negative integer, at least core-lbd",
+    ),
+    ("reduce-fraction", Section::CDCL, "a number in [0, 1]"),
+    (
+        "max-conflicts",
+        Section::Limits,
+        "a non-negative integer or none",
+    ),
+    (
+        "max-propagations",
+        Section::Limits,
+        "a non-negative integer or none",
+    ),
+    (
+        "max-decisions",
+        Section::Limits,
+        "a non-negative integer or none",
+    ),
+    (
+        "timeout",
+        Section::Limits,
+        "a non-negative number of seconds or none",
+    ),
+];
+
+impl Section {
+    pub fn of(key: &str) -> Result<Section, ConfigureError> {
+        KEYS.iter()
+            .find(|(k, _, _)| *k == key)
+            .map(|&(_, section, _)| section)
+            .ok_or_else(|| ConfigureError::UnknownKey(key.to_owned()))
+    }
+}
+
+impl Config {
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::default()
+    }
+
+    pub fn keys() -> impl Iterator<Item = &'static str> {
+        KEYS.iter().map(|&(k, _, _)| k)
+    }
+
+    /// Set the parameter `key` from its textual form.
+    ///
+    /// Fails on values out of the range of the parameter. Constraints between
+    /// parameters are checked by `validate`.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), ConfigureError> {
+        Section::of(key)?;
+        let reduce = &mut self.cdcl.reduce;
+        let limits = &mut self.limits;
+        match key {
+            "heuristic" => self.dpll.heuristic = parse(key, value)?,
+            "phase" => self.cdcl.phase = parse(key, value)?,
+            "seed" => self.cdcl.seed = parse(key, value)?,
+            "restarts" => {
+                self.cdcl.restart = value.trim().parse().map_err(|e| match e {
+                    ParseRestartError::OutOfRange {
+                        parameter,
+                        expected,
+                    } => ConfigureError::InvalidValue {
+                        key: key.to_owned(),
+                        value: value.to_owned(),
+                        expected: format!("a restart {} {}", parameter, expected),
+                    },
+                    ParseRestartError::Unknown(_) => ConfigureError::invalid_value(key, value),
+                })?
+            }
+            "reduce-first" => reduce.first = parse(key, value)?,
+            "reduce-increment" => reduce.increment = parse(key, value)?,
+            "core-lbd" => reduce.core_lbd = parse(key, value)?,
+            "tier2-lbd" => reduce.tier2_lbd = parse(key, value)?,
+            "reduce-fraction" => {
+                let fraction: f64 = parse(key, value)?;
+                if !(0.0..=1.0).contains(&fraction) {
+                    return Err(ConfigureError::invalid_value(key, value));
+                }
+                reduce.fraction = fraction;
+            }
+            "max-conflicts" => limits.conflicts = parse_limit(key, value)?,
+            "max-propagations" => limits.propagations = parse_limit(key, value)?,
+            "max-decisions" => limits.decisions = parse_limit(key, value)?,
+            "timeout" => {
+                let seconds: Option<f64> = parse_limit(key, value)?;
+                limits.time = match seconds {
+                    Some(s) if s.is_finite() && s >= 0.0 => Some(Duration::from_secs_f64(s)),
+                    Some(_) => return Err(ConfigureError::invalid_value(key, value)),
+                    None => None,
+                };
+            }
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+
+    /// Set comma-separated `key=value` pairs, as written by `Display`.
+    ///
+    /// The configuration is left unchanged if any of them is invalid.
+    pub fn set_all(&mut self, pairs: &str) -> Result<(), ConfigureError> {
+        let mut config = self.clone();
+        for (key, value) in parse_pairs(pairs)? {
+            config.set(key, value)?;
+        }
+        config.validate()?;
+        *self = config;
+        Ok(())
+    }
+
+    /// Check the constraints between parameters.
+    pub fn validate(&self) -> Result<(), ConfigureError> {
+        let reduce = &self.cdcl.reduce;
+        if reduce.core_lbd > reduce.tier2_lbd {
+            return Err(ConfigureError::InvalidValue {
+                key: "tier2-lbd".to_owned(),
+                value: reduce.tier2_lbd.to_string(),
+                expected: format!("at least core-lbd ({})", reduce.core_lbd),
+            });
+        }
+        Ok(())
+    }
+
+    fn get(&self, key: &str) -> String {
+        let reduce = &self.cdcl.reduce;
+        let limits = &self.limits;
+        match key {
+            "heuristic" => self.dpll.heuristic.to_string(),
+            "phase" => self.cdcl.phase.to_string(),
+            "seed" => self.cdcl.seed.to_string(),
+            "restarts" => self.cdcl.restart.to_string(),
+            "reduce-first" => reduce.first.to_string(),
+            "reduce-increment" => reduce.increment.to_string(),
+            "core-lbd" => reduce.core_lbd.to_string(),
+            "tier2-lbd" => reduce.tier2_lbd.to_string(),
+            "reduce-fraction" => reduce.fraction.to_string(),
+            "max-conflicts" => display_limit(limits.conflicts),
+            "max-propagations" => display_limit(limits.propagations),
+            "max-decisions" => display_limit(limits.decisions),
+            "timeout" => display_limit(limits.time.map(|t| t.as_secs_f64())),
+            _ => unreachable!(),
+        }
+    }
+
+    /// The parameters of `sections` as comma-separated `key=value` pairs.
+    pub fn to_pairs(&self, sections: &[Section]) -> String {
+        KEYS.iter()
+            .filter(|(_, section, _)| sections.contains(section))
+            .map(|&(key, _, _)| format!("{}={}", key, self.get(key)))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+impl fmt::Display for Config {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sections = [Section::DPLL, Section::CDCL, Section::Limits];
+        f.pad(&self.to_pairs(&sections))
+    }
+}
+
+fn parse<T: str::FromStr>(key: &str, value: &str) -> Result<T, ConfigureError> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| ConfigureError::invalid_value(key, value))
+}
+
+/// Limits are written as a number, or `none` for no limit.
+fn parse_limit<T: str::FromStr>(key: &str, value: &str) -> Result<Option<T>, ConfigureError> {
+    if value.trim().eq_ignore_ascii_case("none") {
+        Ok(None)
+    } else {
+        parse(key, value).map(Some)
+    }
+}
+
+fn display_limit<T: fmt::Display>(limit: Option<T>) -> String {
+    limit.map_or_else(|| "none".to_owned(), |l| l.to_string())
+}
+
+/// Split comma-separated `key=value` pairs.
+pub(super) fn parse_pairs(pairs: &str) -> Result<Vec<(&str, &str)>, ConfigureError> {
+    pairs
+        .split(',')
+        .filter(|p| !p.trim().is_empty())
+        .map(|p| match p.find('=') {
+            Some(i) if i > 0 => Ok((p[..i].trim(), p[i + 1..].trim())),
+            _ => Err(ConfigureError::MalformedPair(p.to_owned())),
+        })
+        .collect()
+}
+
+/// Builds a `Config` from the defaults.
+#[derive(Clone, Debug, Default)]
+pub struct ConfigBuilder {
+    config: Config,
+}
+
+impl ConfigBuilder {
+    pub fn heuristic(mut self, heuristic: Heuristic) -> Self {
+        self.config.dpll.heuristic = heuristic;
+        self
+    }
+
+    pub fn phase(mut self, phase: Phase) -> Self {
+        self.config.cdcl.phase = phase;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.cdcl.seed = seed;
+        self
+    }
+
+    pub fn restart(mut self, restart: Restart) -> Self {
+        self.config.cdcl.restart = restart;
+        self
+    }
+
+    pub fn reduce(mut self, reduce: Reduce) -> Self {
+        self.config.cdcl.reduce = reduce;
+        self
+    }
+
+    pub fn max_conflicts(mut self, conflicts: u64) -> Self {
+        self.config.limits.conflicts = Some(conflicts);
+        self
+    }
+
+    pub fn max_propagations(mut self, propagations: u64) -> Self {
+        self.config.limits.propagations = Some(propagations);
+        self
+    }
+
+    pub fn max_decisions(mut self, decisions: u64) -> Self {
+        self.config.limits.decisions = Some(decisions);
+        self
+    }
+
+    pub fn timeout(mut self, time: Duration) -> Self {
+        self.config.limits.time = Some(time);
+        self
+    }
+
+    /// Set the parameter `key` from its textual form, as `Config::set` does.
+    pub fn set(mut self, key: &str, value: &str) -> Result<Self, ConfigureError> {
+        self.config.set(key, value)?;
+        Ok(self)
+    }
+
+    pub fn build(self) -> Config {
+        self.config
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigureError {
+    UnknownKey(String),
+    UnsupportedKey(String),
+    InvalidValue {
+        key: String,
+        value: String,
+        expected: String,
+    },
+    MalformedPair(String),
+}
+
+impl ConfigureError {
+    /// The value is not one the parameter `key` accepts.
+    pub fn invalid_value(key: &str, value: &str) -> ConfigureError {
+        let expected = KEYS
+            .iter()
+            .find(|(k, _, _)| *k == key)
+            .map_or("", |&(_, _, expected)| expected);
+        ConfigureError::InvalidValue {
+            key: key.to_owned(),
+            value: value.to_owned(),
+            expected: expected.to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigureError::UnknownKey(key) => write!(
+                f,
+                "unknown parameter: {} (expected one of {})",
+                key,
+                Config::keys().collect::<Vec<_>>().join(", ")
+            ),
+            ConfigureError::UnsupportedKey(key) => {
+                write!(f, "parameter {} does not apply to this solver", key)
+            }
+            ConfigureError::InvalidValue {
+                key,
+                value,
+                expected,
+            } => write!(
+                f,
+                "invalid value for {}: {} (expected {})",
+                key, value, expected
+            ),
+            ConfigureError::MalformedPair(p) => {
+                write!(f, "malformed parameter (expected key=value): {}", p)
+            }
+        }
+    }
+}
+
+impl error::Error for ConfigureError {}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Config, ConfigureError};
+    use crate::solver::cdcl::{Phase, Restart};
+    use crate::solver::dpll::Heuristic;
+
+    #[test]
+    fn test_round_trip() {
+        let config = Config::builder()
+            .heuristic(Heuristic::Bohm)
+            .phase(Phase::Random)
+            .seed(42)
+            .restart(Restart::Geometric {
+                initial: 50,
+                factor: 1.25,
+            })
+            .max_conflicts(1000)
+            .timeout(Duration::from_millis(2500))
+            .build();
+
+        let mut parsed = Config::default();
+        parsed.set_all(&config.to_string()).unwrap();
+        assert_eq!(parsed, config);
+
+        parsed.set_all("restarts=glucose,timeout=none").unwrap();
+        assert_eq!(parsed.cdcl.restart, Restart::glucose());
+        assert_eq!(parsed.limits.time, None);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut config = Config::default();
+        match config.set("restart", "luby") {
+            Err(ConfigureError::UnknownKey(key)) => assert_eq!(key, "restart"),
+            _ => panic!("unknown key is accepted"),
+        }
+        match config.set("max-conflicts", "-1") {
+            Err(ConfigureError::InvalidValue { .. }) => {}
+            _ => panic!("invalid value is accepted"),
+        }
+        assert!(config.set("restarts", "luby:often").is_err());
+        assert!(config.set("restarts", "luby:0").is_err());
+        assert!(config.set("restarts", "geometric:100:1").is_err());
+        match config.set("restarts", "glucose:0.5:0.001:0.1") {
+            Err(ConfigureError::InvalidValue { expected, .. }) => {
+                assert!(expected.ends_with("MIN at least 1"), "{}", expected)
+            }
+            _ => panic!("invalid restarts are accepted"),
+        }
+        assert!(config.set("reduce-fraction", "1.5").is_err());
+        assert!(config.set("reduce-first", "-10").is_err());
+        assert!(config.set("timeout", "-3").is_err());
+        assert!(config.set_all("seed").is_err());
+        assert!(config.set_all("seed=1,core-lbd=8").is_err());
+        assert_eq!(config, Config::default());
+
+        config.set_all("core-lbd=8,tier2-lbd=10").unwrap();
+        assert_eq!(config.cdcl.reduce.core_lbd, 8);
+    }
+}
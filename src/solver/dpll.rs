@@ -1,77 +1,345 @@
-use std::collections::HashSet;
+use std::time::Instant;
 
-use crate::assignment::Assignment;
-use crate::cnf::{Literal, CNF};
+use crate::assignment::{Assignment, Truth};
+use crate::cnf::{Literal, Variable, CNF};
+use crate::solver::budget::{Budget, Counts};
+use crate::solver::watch::{self, Propagate, Watches};
+use crate::solver::{self, Limits, Section, SolveResult, Statistics, Status, Terminate};
 
-fn dpll(mut cnf: CNF) -> Option<Assignment> {
-    let mut assignment = Assignment::new();
+use log::info;
 
-    if cnf.is_empty() {
-        return Some(assignment);
+mod config;
+mod heuristic;
+pub use config::Config;
+use heuristic::OpenClauses;
+pub use heuristic::{Heuristic, ParseHeuristicError};
+
+/// A literal on the trail.
+#[derive(Debug, Clone, Copy)]
+struct Assigned {
+    literal: Literal,
+    /// Whether the literal was chosen by branching. Literals implied by unit
+    /// propagation or flipped on backtracking are not decisions.
+    decision: bool,
+}
+
+/// DPLL search over an explicit trail.
+///
+/// Clauses with at least two literals watch their first two literals. On a
+/// conflict, the latest decision that has not been flipped yet is replaced
+/// by its negation, undoing everything assigned after it.
+struct Solver {
+    clauses: Vec<Vec<Literal>>,
+    /// Indices of the clauses watching each literal.
+    watches: Watches<usize>,
+    /// Values of the variables, indexed by variable index.
+    values: Vec<Option<bool>>,
+    variables: Vec<Variable>,
+    trail: Vec<Assigned>,
+    propagation_head: usize,
+    /// Number of decisions on the trail.
+    level: usize,
+    heuristic: Heuristic,
+    open: OpenClauses,
+    statistics: Statistics,
+}
+
+impl Solver {
+    /// Returns `None` if the CNF is trivially unsatisfiable.
+    fn new(cnf: &CNF, heuristic: Heuristic) -> Option<Solver> {
+        let num_vars = cnf
+            .all_clauses()
+            .flatten()
+            .map(|l| l.variable().index() + 1)
+            .max()
+            .unwrap_or(0);
+
+        let mut watches = Watches::new();
+        watches.grow(num_vars);
+        let mut solver = Solver {
+            clauses: Vec::new(),
+            watches,
+            values: vec![None; num_vars],
+            variables: Vec::new(),
+            trail: Vec::new(),
+            propagation_head: 0,
+            level: 0,
+            heuristic,
+            open: OpenClauses::default(),
+            statistics: Statistics {
+                heuristic: Some(heuristic.to_string()),
+                ..Statistics::default()
+            },
+        };
+
+        let mut seen = vec![false; num_vars];
+        for l in cnf.all_clauses().flatten() {
+            let v = l.variable();
+            if !seen[v.index()] {
+                seen[v.index()] = true;
+                solver.variables.push(v);
+            }
+        }
+
+        for clause in cnf.all_clauses() {
+            match clause {
+                [] => return None,
+                [l] => match solver.literal_value(*l) {
+                    Some(false) => return None,
+                    Some(true) => {}
+                    None => solver.assign(*l, false),
+                },
+                _ => {
+                    let index = solver.clauses.len();
+                    solver.watches.watch(clause[0], index);
+                    solver.watches.watch(clause[1], index);
+                    solver.clauses.push(clause.to_vec());
+                }
+            }
+        }
+
+        Some(solver)
     }
 
-    if cnf.has_empty_clause() {
-        return None;
+    fn assign(&mut self, literal: Literal, decision: bool) {
+        self.values[literal.variable().index()] = Some(!literal.is_negated());
+        self.trail.push(Assigned { literal, decision });
     }
 
-    if cnf.impure_literals().next().is_none() {
-        for l in cnf.literals() {
-            assignment.assign_true(l);
+    fn search(&mut self, budget: &Budget) -> Status {
+        loop {
+            if let Some(interrupt) = budget.check(self.counts()) {
+                return Status::Unknown(interrupt);
+            }
+
+            let start = Instant::now();
+            let consistent = self.unit_propagation();
+            self.statistics.times.propagation += start.elapsed();
+
+            if !consistent {
+                self.statistics.conflicts += 1;
+                if !self.backtrack() {
+                    return Status::Unsatisfiable;
+                }
+                continue;
+            }
+
+            let start = Instant::now();
+            let chosen = self.choose_literal();
+            self.statistics.times.decision += start.elapsed();
+
+            match chosen {
+                Some(l) => {
+                    self.statistics.decisions += 1;
+                    self.level += 1;
+                    let max_level = &mut self.statistics.max_decision_level;
+                    *max_level = (*max_level).max(self.level);
+                    self.assign(l, true);
+                }
+                None => return Status::Satisfiable,
+            }
         }
-        return Some(assignment);
     }
 
-    let unit_clauses: Vec<_> = cnf
-        .unit_clauses()
-        .map(|c| c.unit().unwrap().clone())
-        .collect();
-    for l in unit_clauses {
-        assignment.assign_true(&l);
-        cnf.simplify_true_literal(&l);
+    fn counts(&self) -> Counts {
+        Counts {
+            conflicts: self.statistics.conflicts,
+            propagations: self.statistics.propagations,
+            decisions: self.statistics.decisions,
+        }
     }
 
-    let literals: HashSet<_> = cnf.literals().cloned().collect();
-    let impure_literals: HashSet<_> = cnf.impure_literals().collect();
-    for l in literals.difference(&impure_literals) {
-        assignment.assign_true(&l);
-        cnf.simplify_true_literal(&l);
+    /// Propagate the assignments on the trail through the watch lists,
+    /// returning `false` on a conflict.
+    fn unit_propagation(&mut self) -> bool {
+        while self.propagation_head < self.trail.len() {
+            let false_lit = !self.trail[self.propagation_head].literal;
+            self.propagation_head += 1;
+
+            if watch::propagate(self, false_lit).is_some() {
+                return false;
+            }
+        }
+
+        true
     }
 
-    let result = if let Some(l) = choose_literal(&cnf) {
-        branch(cnf, &l)
-    } else {
-        dpll(cnf)
-    };
+    /// Undo the trail up to the latest decision and assign its negation.
+    ///
+    /// Returns `false` if there is no decision left to flip.
+    fn backtrack(&mut self) -> bool {
+        while let Some(Assigned { literal, decision }) = self.trail.pop() {
+            self.values[literal.variable().index()] = None;
+            if decision {
+                self.level -= 1;
+                self.propagation_head = self.trail.len();
+                self.assign(!literal, false);
+                return true;
+            }
+        }
+        false
+    }
 
-    if let Some(a) = result {
-        assignment.extend(a);
-        Some(assignment)
-    } else {
-        None
+    /// The literal chosen by the heuristic, or `None` once every clause is satisfied.
+    fn choose_literal(&mut self) -> Option<Literal> {
+        self.open.clear();
+        for clause in &self.clauses {
+            if clause.iter().any(|&l| self.literal_value(l) == Some(true)) {
+                continue;
+            }
+            let values = &self.values;
+            self.open.push(
+                clause
+                    .iter()
+                    .copied()
+                    .filter(|l| values[l.variable().index()].is_none()),
+            );
+        }
+        self.heuristic.choose(&self.open, self.values.len())
     }
-}
 
-fn choose_literal(cnf: &CNF) -> Option<Literal> {
-    cnf.most_occurred_literal().cloned()
+    /// The model found by `search`, assigning `False` to variables left unassigned.
+    fn model(&self) -> Assignment {
+        let mut assignment = Assignment::new();
+        for v in &self.variables {
+            let value = self.values[v.index()].unwrap_or(false);
+            assignment.assign(v, Truth::from(value));
+        }
+        assignment
+    }
 }
 
-fn branch(mut cnf: CNF, l: &Literal) -> Option<Assignment> {
-    let mut pos = cnf.clone();
-    pos.simplify_true_literal(l);
-    dpll(pos).map(|a| a.assigned_true(l)).or_else(|| {
-        let neg = l.negated();
-        cnf.simplify_true_literal(&neg);
-        dpll(cnf).map(|a| a.assigned_true(&neg))
-    })
+impl Propagate for Solver {
+    type Literal = Literal;
+    type Clause = usize;
+
+    fn watches_mut(&mut self) -> &mut Watches<usize> {
+        &mut self.watches
+    }
+
+    fn clause(&self, index: usize) -> &[Literal] {
+        &self.clauses[index]
+    }
+
+    fn clause_mut(&mut self, index: usize) -> &mut [Literal] {
+        &mut self.clauses[index]
+    }
+
+    fn literal_value(&self, literal: Literal) -> Option<bool> {
+        self.values[literal.variable().index()].map(|v| v != literal.is_negated())
+    }
+
+    fn imply(&mut self, literal: Literal, _reason: usize) {
+        self.statistics.propagations += 1;
+        self.assign(literal, false);
+    }
 }
 
 pub fn solve(cnf: CNF) -> Option<Assignment> {
-    dpll(cnf)
+    let (model, statistics) = solve_with_heuristic(cnf, Heuristic::default());
+    info!("{}", statistics);
+    model
+}
+
+pub fn solve_with_heuristic(cnf: CNF, heuristic: Heuristic) -> (Option<Assignment>, Statistics) {
+    let (result, statistics) = solve_with_limits(cnf, heuristic, Limits::default());
+    (result.into_model(), statistics)
+}
+
+pub fn solve_with_limits(
+    cnf: CNF,
+    heuristic: Heuristic,
+    limits: Limits,
+) -> (SolveResult, Statistics) {
+    run(
+        cnf,
+        heuristic,
+        Budget::start(limits, None, Counts::default()),
+    )
+}
+
+fn run(cnf: CNF, heuristic: Heuristic, budget: Budget) -> (SolveResult, Statistics) {
+    let mut solver = match Solver::new(&cnf, heuristic) {
+        Some(solver) => solver,
+        None => {
+            let statistics = Statistics {
+                heuristic: Some(heuristic.to_string()),
+                ..Statistics::default()
+            };
+            return (SolveResult::Unsatisfiable, statistics);
+        }
+    };
+    let start = Instant::now();
+    let status = solver.search(&budget);
+    solver.statistics.times.total = start.elapsed();
+    let result = match status {
+        Status::Satisfiable => SolveResult::Satisfiable(solver.model()),
+        Status::Unsatisfiable => SolveResult::Unsatisfiable,
+        Status::Unknown(interrupt) => SolveResult::Unknown(interrupt),
+    };
+    (result, solver.statistics)
+}
+
+/// The DPLL solver behind the `solver::Solver` trait.
+#[derive(Default)]
+pub struct Engine {
+    config: solver::Config,
+    terminate: Option<Terminate>,
+    statistics: Statistics,
+}
+
+impl Engine {
+    pub fn new(config: solver::Config) -> Engine {
+        Engine {
+            config,
+            terminate: None,
+            statistics: Statistics::default(),
+        }
+    }
+}
+
+impl super::Solver for Engine {
+    fn run(&mut self, cnf: CNF) -> SolveResult {
+        let budget = Budget::start(
+            self.config.limits,
+            self.terminate.clone(),
+            Counts::default(),
+        );
+        let (result, statistics) = run(cnf, self.config.dpll.heuristic, budget);
+        self.statistics = statistics;
+        result
+    }
+
+    fn statistics(&self) -> &Statistics {
+        &self.statistics
+    }
+
+    fn set_terminate(&mut self, terminate: Option<Terminate>) {
+        self.terminate = terminate;
+    }
+
+    fn config(&self) -> &solver::Config {
+        &self.config
+    }
+
+    fn config_mut(&mut self) -> &mut solver::Config {
+        &mut self.config
+    }
+
+    fn sections(&self) -> &[Section] {
+        &[Section::DPLL, Section::Limits]
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::solve;
+    use std::collections::HashSet;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+
+    use super::{solve, solve_with_heuristic, solve_with_limits, Engine, Heuristic};
+    use crate::dimacs;
+    use crate::solver::{Interrupt, Limits, Solver, Status, Terminate};
 
     #[test]
     fn test_excluded_middle() {
@@ -97,4 +365,48 @@ mod tests {
         )
         .is_none());
     }
+
+    #[test]
+    fn test_heuristics() {
+        let sat =
+            dimacs::parse(include_bytes!("../../benches/data/aim-50-1_6-yes1-4.cnf").as_ref())
+                .unwrap();
+        let unsat = dimacs::parse(include_bytes!("../../benches/data/hole6.cnf").as_ref()).unwrap();
+
+        let mut decisions = HashSet::new();
+        for heuristic in Heuristic::all() {
+            let (model, statistics) = solve_with_heuristic(sat.clone(), heuristic);
+            assert!(model.is_some(), "{}", heuristic);
+            assert_eq!(statistics.heuristic, Some(heuristic.to_string()));
+            assert_eq!(statistics.rows()[0].0, format!("decisions ({})", heuristic));
+            // the instance tells every heuristic apart
+            assert!(decisions.insert(statistics.decisions), "{}", heuristic);
+
+            let (model, statistics) = solve_with_heuristic(unsat.clone(), heuristic);
+            assert!(model.is_none(), "{}", heuristic);
+            assert!(statistics.conflicts > 0);
+        }
+    }
+
+    #[test]
+    fn test_limits() {
+        let cnf = dimacs::parse(include_bytes!("../../benches/data/hole6.cnf").as_ref()).unwrap();
+        let limits = Limits {
+            decisions: Some(100),
+            ..Limits::default()
+        };
+        let (result, statistics) = solve_with_limits(cnf, Heuristic::default(), limits);
+        assert_eq!(result.status(), Status::Unknown(Interrupt::Decisions));
+        assert_eq!(statistics.decisions, 100);
+    }
+
+    #[test]
+    fn test_terminate() {
+        let cnf = dimacs::parse(include_bytes!("../../benches/data/hole6.cnf").as_ref()).unwrap();
+        let mut engine = Engine::default();
+        let flag = Arc::new(AtomicBool::new(true));
+        engine.set_terminate(Some(Terminate::flag(flag)));
+        let result = engine.run(cnf);
+        assert_eq!(result.status(), Status::Unknown(Interrupt::Cancelled));
+    }
 }
@@ -0,0 +1,6 @@
+use super::Heuristic;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Config {
+    pub heuristic: Heuristic,
+}
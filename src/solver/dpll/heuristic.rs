@@ -0,0 +1,248 @@
+use std::{error, fmt, str};
+
+use crate::cnf::{Literal, Variable};
+
+/// How the literal to branch on is chosen.
+///
+/// Every heuristic looks at the open clauses, that is, the clauses not
+/// satisfied yet, restricted to their unassigned literals.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Heuristic {
+    /// Maximum occurrences in clauses of minimum size.
+    MOMS,
+    /// One-sided Jeroslow-Wang: the literal maximizing the sum of `2^-|c|`
+    /// over the clauses containing it.
+    JeroslowWang,
+    /// Two-sided Jeroslow-Wang: the variable maximizing the sum of both
+    /// literals' Jeroslow-Wang scores.
+    TwoSidedJeroslowWang,
+    /// Dynamic largest individual sum: the literal occurring the most.
+    DLIS,
+    /// Dynamic largest combined sum: the variable occurring the most.
+    DLCS,
+    /// Böhm's heuristic: the variable with the lexicographically largest
+    /// vector of occurrences by clause size, shortest clauses first.
+    Bohm,
+}
+
+// `#[default]` on variants is too new for the toolchain of the crate
+#[allow(clippy::derivable_impls)]
+impl Default for Heuristic {
+    fn default() -> Heuristic {
+        Heuristic::DLIS
+    }
+}
+
+/// Weight of the sum of both literals' occurrences in the MOMS score.
+const MOMS_SHIFT: u32 = 10;
+/// Weights of the larger and the smaller count of occurrences in Böhm's score.
+const BOHM_ALPHA: u64 = 1;
+const BOHM_BETA: u64 = 2;
+
+impl Heuristic {
+    pub fn all() -> impl Iterator<Item = Heuristic> {
+        vec![
+            Heuristic::MOMS,
+            Heuristic::JeroslowWang,
+            Heuristic::TwoSidedJeroslowWang,
+            Heuristic::DLIS,
+            Heuristic::DLCS,
+            Heuristic::Bohm,
+        ]
+        .into_iter()
+    }
+
+    /// The literal to branch on, or `None` if there is no open clause.
+    pub(super) fn choose(self, open: &OpenClauses, num_vars: usize) -> Option<Literal> {
+        if open.is_empty() {
+            return None;
+        }
+
+        match self {
+            Heuristic::MOMS => {
+                let min_len = open.iter().map(<[_]>::len).min().unwrap();
+                let counts =
+                    occurrences(open, num_vars, |len| if len == min_len { 1.0 } else { 0.0 });
+                best_variable(&counts, |p, n| (p + n) * f64::from(1 << MOMS_SHIFT) + p * n)
+            }
+            Heuristic::JeroslowWang => {
+                best_literal(&occurrences(open, num_vars, jeroslow_wang_weight))
+            }
+            Heuristic::TwoSidedJeroslowWang => best_variable(
+                &occurrences(open, num_vars, jeroslow_wang_weight),
+                |p, n| p + n,
+            ),
+            Heuristic::DLIS => best_literal(&occurrences(open, num_vars, |_| 1.0)),
+            Heuristic::DLCS => best_variable(&occurrences(open, num_vars, |_| 1.0), |p, n| p + n),
+            Heuristic::Bohm => bohm(open, num_vars),
+        }
+    }
+}
+
+fn jeroslow_wang_weight(len: usize) -> f64 {
+    (0.5f64).powi(len as i32)
+}
+
+/// Weighted occurrences of each literal in the open clauses, indexed by literal.
+fn occurrences(open: &OpenClauses, num_vars: usize, weight: impl Fn(usize) -> f64) -> Vec<f64> {
+    let mut counts = vec![0.0; num_vars * 2];
+    for clause in open.iter() {
+        let w = weight(clause.len());
+        for l in clause {
+            counts[l.index()] += w;
+        }
+    }
+    counts
+}
+
+/// The first literal with the highest positive count.
+fn best_literal(counts: &[f64]) -> Option<Literal> {
+    let mut best = None;
+    let mut best_count = 0.0;
+    for (i, &count) in counts.iter().enumerate() {
+        if count > best_count {
+            best = Some(Literal::from_index(i));
+            best_count = count;
+        }
+    }
+    best
+}
+
+/// The first variable with the highest score among those occurring, in the
+/// polarity occurring the most.
+fn best_variable(counts: &[f64], score: impl Fn(f64, f64) -> f64) -> Option<Literal> {
+    let mut best = None;
+    let mut best_score = f64::NEG_INFINITY;
+    for (v, c) in counts.chunks(2).enumerate() {
+        let (p, n) = (c[0], c[1]);
+        if p + n == 0.0 {
+            continue;
+        }
+        let s = score(p, n);
+        if s > best_score {
+            best = Some(Literal::new(Variable::from_index(v), n > p));
+            best_score = s;
+        }
+    }
+    best
+}
+
+/// Compare the candidate variables on the Böhm score of each clause size in
+/// increasing order, until a single one is left.
+///
+/// The polarity is the one occurring more in the shortest clauses where the
+/// two differ.
+fn bohm(open: &OpenClauses, num_vars: usize) -> Option<Literal> {
+    let mut sizes: Vec<_> = open.iter().map(<[_]>::len).collect();
+    sizes.sort_unstable();
+    sizes.dedup();
+
+    let all = occurrences(open, num_vars, |_| 1.0);
+    let mut candidates: Vec<_> = (0..num_vars)
+        .filter(|&v| all[2 * v] + all[2 * v + 1] > 0.0)
+        .collect();
+    let mut negated = vec![None; num_vars];
+
+    for size in sizes {
+        if candidates.len() == 1 {
+            break;
+        }
+
+        let mut counts = vec![0u64; num_vars * 2];
+        for clause in open.iter().filter(|c| c.len() == size) {
+            for l in clause {
+                counts[l.index()] += 1;
+            }
+        }
+
+        let score = |v: usize| {
+            let (p, n) = (counts[2 * v], counts[2 * v + 1]);
+            BOHM_ALPHA * p.max(n) + BOHM_BETA * p.min(n)
+        };
+        let best = candidates.iter().map(|&v| score(v)).max().unwrap();
+        candidates.retain(|&v| score(v) == best);
+
+        for &v in &candidates {
+            let (p, n) = (counts[2 * v], counts[2 * v + 1]);
+            if negated[v].is_none() && p != n {
+                negated[v] = Some(n > p);
+            }
+        }
+    }
+
+    let v = *candidates.first()?;
+    Some(Literal::new(
+        Variable::from_index(v),
+        negated[v].unwrap_or(false),
+    ))
+}
+
+/// Unassigned literals of the clauses not satisfied yet, stored contiguously
+/// so that the buffers are reused between decisions.
+#[derive(Debug, Default)]
+pub(super) struct OpenClauses {
+    literals: Vec<Literal>,
+    ends: Vec<usize>,
+}
+
+impl OpenClauses {
+    pub fn clear(&mut self) {
+        self.literals.clear();
+        self.ends.clear();
+    }
+
+    pub fn push(&mut self, literals: impl IntoIterator<Item = Literal>) {
+        self.literals.extend(literals);
+        self.ends.push(self.literals.len());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &[Literal]> {
+        let starts = std::iter::once(0).chain(self.ends.iter().copied());
+        starts
+            .zip(self.ends.iter().copied())
+            .map(move |(start, end)| &self.literals[start..end])
+    }
+}
+
+impl fmt::Display for Heuristic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Heuristic::MOMS => f.pad("MOMS"),
+            Heuristic::JeroslowWang => f.pad("JW"),
+            Heuristic::TwoSidedJeroslowWang => f.pad("JW2"),
+            Heuristic::DLIS => f.pad("DLIS"),
+            Heuristic::DLCS => f.pad("DLCS"),
+            Heuristic::Bohm => f.pad("Bohm"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseHeuristicError(String);
+
+impl fmt::Display for ParseHeuristicError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown branching heuristic: {}", self.0)
+    }
+}
+
+impl error::Error for ParseHeuristicError {}
+
+impl str::FromStr for Heuristic {
+    type Err = ParseHeuristicError;
+    fn from_str(s: &str) -> Result<Heuristic, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "moms" => Ok(Heuristic::MOMS),
+            "jw" => Ok(Heuristic::JeroslowWang),
+            "jw2" => Ok(Heuristic::TwoSidedJeroslowWang),
+            "dlis" => Ok(Heuristic::DLIS),
+            "dlcs" => Ok(Heuristic::DLCS),
+            "bohm" => Ok(Heuristic::Bohm),
+            _ => Err(ParseHeuristicError(s.to_owned())),
+        }
+    }
+}
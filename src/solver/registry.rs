@@ -0,0 +1,312 @@
+use std::rc::Rc;
+use std::{error, fmt, str};
+
+use super::config::parse_pairs;
+use super::{cdcl, dpll, ConfigureError, Solver};
+
+use itertools::Itertools;
+
+/// A solver name with parameters, written as `name` or `name:key=value,...`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SolverSpec {
+    name: String,
+    params: Vec<(String, String)>,
+}
+
+impl SolverSpec {
+    pub fn new(name: &str) -> SolverSpec {
+        SolverSpec {
+            name: name.to_lowercase(),
+            params: Vec::new(),
+        }
+    }
+
+    pub fn with_param(mut self, key: &str, value: &str) -> SolverSpec {
+        self.params.push((key.to_owned(), value.to_owned()));
+        self
+    }
+
+    /// Add comma-separated `key=value` pairs.
+    pub fn with_params(mut self, pairs: &str) -> Result<SolverSpec, ParseSolverError> {
+        let pairs = parse_pairs(pairs).map_err(|e| match e {
+            ConfigureError::MalformedPair(p) => ParseSolverError::MalformedParameter(p),
+            _ => unreachable!(),
+        })?;
+        for (key, value) in pairs {
+            self = self.with_param(key, value);
+        }
+        Ok(self)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn params(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.params.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+}
+
+impl fmt::Display for SolverSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.params.is_empty() {
+            return f.pad(&self.name);
+        }
+        let params = self.params().map(|(k, v)| format!("{}={}", k, v)).join(",");
+        f.pad(&format!("{}:{}", self.name, params))
+    }
+}
+
+#[derive(Debug)]
+pub enum ParseSolverError {
+    EmptyName,
+    MalformedParameter(String),
+}
+
+impl fmt::Display for ParseSolverError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseSolverError::EmptyName => write!(f, "empty solver name"),
+            ParseSolverError::MalformedParameter(p) => {
+                write!(f, "malformed solver parameter (expected key=value): {}", p)
+            }
+        }
+    }
+}
+
+impl error::Error for ParseSolverError {}
+
+impl str::FromStr for SolverSpec {
+    type Err = ParseSolverError;
+    fn from_str(s: &str) -> Result<SolverSpec, Self::Err> {
+        let (name, params) = match s.find(':') {
+            Some(i) => (&s[..i], Some(&s[i + 1..])),
+            None => (s, None),
+        };
+        if name.trim().is_empty() {
+            return Err(ParseSolverError::EmptyName);
+        }
+
+        SolverSpec::new(name.trim()).with_params(params.unwrap_or(""))
+    }
+}
+
+#[derive(Debug)]
+pub enum RegistryError {
+    UnknownSolver {
+        name: String,
+        available: Vec<String>,
+    },
+    Configure {
+        spec: String,
+        error: ConfigureError,
+    },
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RegistryError::UnknownSolver { name, available } => write!(
+                f,
+                "unknown solver: {} (available: {})",
+                name,
+                available.join(", ")
+            ),
+            RegistryError::Configure { spec, error } => write!(f, "{}: {}", spec, error),
+        }
+    }
+}
+
+impl error::Error for RegistryError {}
+
+type Factory = Rc<dyn Fn() -> Box<dyn Solver>>;
+
+struct Entry {
+    name: String,
+    factory: Factory,
+    params: Vec<(String, String)>,
+}
+
+/// Solvers by name.
+///
+/// `Registry::default()` holds the built-in `dpll` and `cdcl` solvers. Other
+/// engines are added with `register`, and configured variants of registered
+/// solvers with `register_variant`.
+pub struct Registry {
+    entries: Vec<Entry>,
+}
+
+impl Registry {
+    pub fn new() -> Registry {
+        Registry {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Register an engine, replacing any solver of the same name.
+    pub fn register<F>(&mut self, name: &str, factory: F)
+    where
+        F: Fn() -> Box<dyn Solver> + 'static,
+    {
+        self.insert(Entry {
+            name: name.to_lowercase(),
+            factory: Rc::new(factory),
+            params: Vec::new(),
+        });
+    }
+
+    /// Register `spec` under a new name, so that `name` creates the solver of
+    /// `spec` with its parameters.
+    pub fn register_variant(&mut self, name: &str, spec: &SolverSpec) -> Result<(), RegistryError> {
+        let base = self.entry(spec.name())?;
+        let mut params = base.params.clone();
+        params.extend(spec.params.iter().cloned());
+        let entry = Entry {
+            name: name.to_lowercase(),
+            factory: Rc::clone(&base.factory),
+            params,
+        };
+
+        // fail early on parameters the engine does not accept
+        let mut solver = (entry.factory)();
+        configure(solver.as_mut(), spec, &entry.params)?;
+        validate(solver.as_ref(), spec)?;
+
+        self.insert(entry);
+        Ok(())
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|e| e.name.as_str())
+    }
+
+    /// Create the solver named by `spec`, configured with the parameters of
+    /// its registered variant followed by those of `spec`.
+    pub fn create(&self, spec: &SolverSpec) -> Result<Box<dyn Solver>, RegistryError> {
+        let entry = self.entry(spec.name())?;
+        let mut solver = (entry.factory)();
+        configure(solver.as_mut(), spec, &entry.params)?;
+        configure(solver.as_mut(), spec, &spec.params)?;
+        validate(solver.as_ref(), spec)?;
+        Ok(solver)
+    }
+
+    fn entry(&self, name: &str) -> Result<&Entry, RegistryError> {
+        let name = name.to_lowercase();
+        self.entries
+            .iter()
+            .find(|e| e.name == name)
+            .ok_or_else(|| RegistryError::UnknownSolver {
+                name,
+                available: self.names().map(str::to_owned).collect(),
+            })
+    }
+
+    fn insert(&mut self, entry: Entry) {
+        self.entries.retain(|e| e.name != entry.name);
+        self.entries.push(entry);
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Registry {
+        let mut registry = Registry::new();
+        registry.register("dpll", || Box::new(dpll::Engine::default()));
+        registry.register("cdcl", || Box::new(cdcl::Engine::default()));
+        registry
+    }
+}
+
+fn configure(
+    solver: &mut dyn Solver,
+    spec: &SolverSpec,
+    params: &[(String, String)],
+) -> Result<(), RegistryError> {
+    for (key, value) in params {
+        solver
+            .configure(key, value)
+            .map_err(|error| RegistryError::Configure {
+                spec: spec.to_string(),
+                error,
+            })?;
+    }
+    Ok(())
+}
+
+fn validate(solver: &dyn Solver, spec: &SolverSpec) -> Result<(), RegistryError> {
+    solver
+        .config()
+        .validate()
+        .map_err(|error| RegistryError::Configure {
+            spec: spec.to_string(),
+            error,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Registry, RegistryError, SolverSpec};
+
+    #[test]
+    fn test_parse_spec() {
+        let spec: SolverSpec = "CDCL:restarts=luby, phase = saved".parse().unwrap();
+        assert_eq!(spec.name(), "cdcl");
+        assert_eq!(
+            spec.params().collect::<Vec<_>>(),
+            vec![("restarts", "luby"), ("phase", "saved")]
+        );
+        assert_eq!(spec.to_string(), "cdcl:restarts=luby,phase=saved");
+
+        assert!("".parse::<SolverSpec>().is_err());
+        assert!("cdcl:restarts".parse::<SolverSpec>().is_err());
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut registry = Registry::default();
+        let formula = "A \\/ B /\\ !A \\/ B /\\ A \\/ !B";
+
+        for name in ["dpll", "cdcl"].iter() {
+            let mut solver = registry.create(&name.parse().unwrap()).unwrap();
+            assert!(solver.run(formula.parse().unwrap()).model().is_some());
+        }
+
+        let luby: SolverSpec = "cdcl:restarts=luby".parse().unwrap();
+        registry.register_variant("luby", &luby).unwrap();
+        assert!(registry.names().any(|n| n == "luby"));
+        assert!(registry.create(&SolverSpec::new("luby")).is_ok());
+
+        match registry.create(&"minisat".parse().unwrap()) {
+            Err(RegistryError::UnknownSolver { .. }) => {}
+            _ => panic!("unknown solver is accepted"),
+        }
+        match registry.create(&"cdcl:restarts=often".parse().unwrap()) {
+            Err(RegistryError::Configure { .. }) => {}
+            _ => panic!("invalid parameter is accepted"),
+        }
+        assert!(registry
+            .create(&"cdcl:core-lbd=8,tier2-lbd=4".parse().unwrap())
+            .is_err());
+        assert!(registry
+            .create(&"cdcl:core-lbd=8,tier2-lbd=10".parse().unwrap())
+            .is_ok());
+        assert!(registry
+            .register_variant("bad", &"dpll:restarts=luby".parse().unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn test_statistics_of_last_run() {
+        let registry = Registry::default();
+        let formula = "A \\/ B /\\ !A \\/ B /\\ A \\/ !B";
+
+        for name in ["dpll", "cdcl"].iter() {
+            let mut solver = registry.create(&name.parse().unwrap()).unwrap();
+            solver.run(formula.parse().unwrap());
+            let first = solver.statistics().decisions;
+            assert!(first > 0, "{}", name);
+            solver.run(formula.parse().unwrap());
+            assert_eq!(solver.statistics().decisions, first, "{}", name);
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use std::fmt;
+use std::time::Duration;
+
+/// Counters of a solver.
+///
+/// Engines behind `Solver` count each run from zero, while the incremental
+/// `cdcl::Solver` adds up the counters of all its calls.
+///
+/// Solvers leave the counters of what they do not perform at zero, such as
+/// learned clauses in DPLL.
+#[derive(Default, Clone, Debug)]
+pub struct Statistics {
+    /// Branching heuristic the decisions were made by, for solvers with a choice of them.
+    pub heuristic: Option<String>,
+    pub decisions: u64,
+    pub propagations: u64,
+    pub conflicts: u64,
+    pub restarts: u64,
+    pub learned_clauses: u64,
+    pub learned_literals: u64,
+    /// Sum of the LBD of learned clauses.
+    pub learned_lbd: u64,
+    pub minimized_literals: u64,
+    pub deleted_clauses: u64,
+    pub max_decision_level: usize,
+    pub times: Times,
+}
+
+/// Time spent in each phase of the search.
+#[derive(Default, Clone, Copy, Debug)]
+pub struct Times {
+    /// The whole search, including the phases below.
+    pub total: Duration,
+    pub propagation: Duration,
+    pub decision: Duration,
+    pub analysis: Duration,
+    pub reduction: Duration,
+}
+
+impl Statistics {
+    /// Average size of learned clauses after minimization.
+    pub fn average_learned_size(&self) -> f64 {
+        average(self.learned_literals, self.learned_clauses)
+    }
+
+    pub fn average_learned_lbd(&self) -> f64 {
+        average(self.learned_lbd, self.learned_clauses)
+    }
+
+    /// Names and formatted values of the statistics, in the order they are displayed.
+    ///
+    /// Decisions are labeled with the heuristic, as in `decisions (DLIS)`.
+    pub fn rows(&self) -> Vec<(String, String)> {
+        let seconds = |d: Duration| format!("{:.3} s", d.as_secs_f64());
+        let decisions = match &self.heuristic {
+            Some(heuristic) => format!("decisions ({})", heuristic),
+            None => "decisions".to_owned(),
+        };
+        let rows = vec![
+            (decisions.as_str(), self.decisions.to_string()),
+            ("propagations", self.propagations.to_string()),
+            ("conflicts", self.conflicts.to_string()),
+            ("restarts", self.restarts.to_string()),
+            ("learned clauses", self.learned_clauses.to_string()),
+            (
+                "avg. learned size",
+                format!("{:.2}", self.average_learned_size()),
+            ),
+            (
+                "avg. learned LBD",
+                format!("{:.2}", self.average_learned_lbd()),
+            ),
+            ("minimized literals", self.minimized_literals.to_string()),
+            ("deleted clauses", self.deleted_clauses.to_string()),
+            ("max. decision level", self.max_decision_level.to_string()),
+            ("total time", seconds(self.times.total)),
+            ("propagation time", seconds(self.times.propagation)),
+            ("decision time", seconds(self.times.decision)),
+            ("analysis time", seconds(self.times.analysis)),
+            ("reduction time", seconds(self.times.reduction)),
+        ];
+        rows.into_iter()
+            .map(|(name, value)| (name.to_owned(), value))
+            .collect()
+    }
+}
+
+fn average(sum: u64, count: u64) -> f64 {
+    if count == 0 {
+        0.0
+    } else {
+        sum as f64 / count as f64
+    }
+}
+
+impl fmt::Display for Statistics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rows: Vec<_> = self
+            .rows()
+            .into_iter()
+            .map(|(name, value)| format!("{}: {}", name, value))
+            .collect();
+        f.pad(&rows.join(", "))
+    }
+}
@@ -0,0 +1,143 @@
+use std::{mem, ops};
+
+use crate::cnf::{ClauseRef, Literal, Relocation};
+
+/// Literals with a dense index, by which watch lists are looked up.
+pub trait WatchedLiteral: Copy + Eq + ops::Not<Output = Self> {
+    fn index(self) -> usize;
+}
+
+impl WatchedLiteral for Literal {
+    fn index(self) -> usize {
+        Literal::index(self)
+    }
+}
+
+/// Watch lists of the clauses `C`, indexed by literal.
+///
+/// A clause is registered under each of its first two literals and is visited
+/// only when one of them becomes false.
+pub struct Watches<C> {
+    lists: Vec<Vec<C>>,
+}
+
+impl<C: Copy> Watches<C> {
+    pub fn new() -> Self {
+        Watches { lists: Vec::new() }
+    }
+
+    pub fn grow(&mut self, num_vars: usize) {
+        self.lists.resize_with(num_vars * 2, Vec::new);
+    }
+
+    pub fn watch<L: WatchedLiteral>(&mut self, literal: L, clause: C) {
+        self.lists[literal.index()].push(clause);
+    }
+
+    /// Take the watch list of `literal` out, to be put back with `restore`.
+    pub fn take<L: WatchedLiteral>(&mut self, literal: L) -> Vec<C> {
+        mem::take(&mut self.lists[literal.index()])
+    }
+
+    pub fn restore<L: WatchedLiteral>(&mut self, literal: L, watchers: Vec<C>) {
+        debug_assert!(self.lists[literal.index()].is_empty());
+        self.lists[literal.index()] = watchers;
+    }
+
+    /// Drop the watches of clauses for which `f` returns false.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&C) -> bool,
+    {
+        for list in &mut self.lists {
+            list.retain(|clause| f(clause));
+        }
+    }
+}
+
+impl Watches<ClauseRef> {
+    /// Follow the clauses moved by garbage collection; all of them must be kept.
+    pub fn relocate(&mut self, relocation: &Relocation) {
+        for list in &mut self.lists {
+            for cref in list {
+                *cref = relocation.get(*cref).unwrap();
+            }
+        }
+    }
+}
+
+/// A solver whose clauses of at least two literals watch their first two.
+pub trait Propagate {
+    type Literal: WatchedLiteral;
+    type Clause: Copy;
+
+    fn watches_mut(&mut self) -> &mut Watches<Self::Clause>;
+
+    fn clause(&self, clause: Self::Clause) -> &[Self::Literal];
+
+    fn clause_mut(&mut self, clause: Self::Clause) -> &mut [Self::Literal];
+
+    fn literal_value(&self, literal: Self::Literal) -> Option<bool>;
+
+    /// Assign the last unassigned literal of `reason`, the others being false.
+    fn imply(&mut self, literal: Self::Literal, reason: Self::Clause);
+}
+
+/// Visit the clauses watching `false_lit`, which has just become false.
+///
+/// Each clause either finds another literal to watch, is already satisfied by
+/// its other watched literal, or implies that literal. Returns the clause all
+/// of whose literals are false, if any, leaving the rest of the list unvisited.
+pub fn propagate<P: Propagate>(solver: &mut P, false_lit: P::Literal) -> Option<P::Clause> {
+    let mut watchers = solver.watches_mut().take(false_lit);
+    let mut conflict = None;
+
+    let mut kept = 0;
+    let mut i = 0;
+    while i < watchers.len() {
+        let clause = watchers[i];
+        i += 1;
+
+        let literals = solver.clause_mut(clause);
+        if literals[0] == false_lit {
+            literals.swap(0, 1);
+        }
+        let first = literals[0];
+        let first_value = solver.literal_value(first);
+        if first_value == Some(true) {
+            watchers[kept] = clause;
+            kept += 1;
+            continue;
+        }
+
+        let literals = solver.clause(clause);
+        if let Some(k) =
+            (2..literals.len()).find(|&k| solver.literal_value(literals[k]) != Some(false))
+        {
+            let literals = solver.clause_mut(clause);
+            literals.swap(1, k);
+            let watched = literals[1];
+            solver.watches_mut().watch(watched, clause);
+            continue;
+        }
+
+        watchers[kept] = clause;
+        kept += 1;
+
+        if first_value == Some(false) {
+            conflict = Some(clause);
+            while i < watchers.len() {
+                watchers[kept] = watchers[i];
+                kept += 1;
+                i += 1;
+            }
+            break;
+        }
+
+        solver.imply(first, clause);
+    }
+
+    watchers.truncate(kept);
+    solver.watches_mut().restore(false_lit, watchers);
+    conflict
+}
@@ -1,4 +1,4 @@
-use crate::cnf::{Clause, Literal, Variable, CNF};
+use crate::cnf::{Clause, Literal, NameTable, Variable, CNF};
 use crate::expr::Expr;
 
 use itertools::Either::{Left, Right};
@@ -42,6 +42,7 @@ impl Simple {
 
 struct Convert {
     clauses: Vec<Clause>,
+    names: NameTable,
     unique: usize,
 }
 
@@ -49,17 +50,18 @@ impl Convert {
     fn new() -> Convert {
         Convert {
             clauses: Vec::new(),
+            names: NameTable::new(),
             unique: 0,
         }
     }
 
     fn finalize(self, s: Simple) -> CNF {
-        CNF::from_clauses(self.clauses.into_iter().chain(s.into_clauses()))
+        CNF::with_names(self.clauses.into_iter().chain(s.into_clauses()), self.names)
     }
 
     fn simplify(&mut self, expr: Expr) -> Simple {
         match expr {
-            Expr::Var(v) => Simple::Lit(v.into()),
+            Expr::Var(name) => Simple::Lit(self.names.intern(&name).into()),
             Expr::Not(box e) => {
                 let v = self.subexpr_substitution(e);
                 Simple::Lit(Literal::new(v, true))
@@ -78,8 +80,8 @@ impl Convert {
     }
 
     fn subexpr_substitution(&mut self, e: Expr) -> Variable {
-        if let Expr::Var(v) = e {
-            return v;
+        if let Expr::Var(name) = e {
+            return self.names.intern(&name);
         }
 
         let s = self.simplify(e);
@@ -90,9 +92,9 @@ impl Convert {
 
     fn substitute(&mut self, v: &Variable, s: Simple) {
         // ¬s ∨ v
-        self.introduce_or(v.clone().into(), s.clone().negated());
+        self.introduce_or((*v).into(), s.clone().negated());
         // ¬v ∨ s
-        self.introduce_or(Literal::new(v.clone(), true), s);
+        self.introduce_or(Literal::new(*v, true), s);
     }
 
     /// introduce `l1 ∨ s`
@@ -106,7 +108,7 @@ impl Convert {
             Simple::And(l2, l3) => {
                 // l1 ∨ (l2 ∧ l3)
                 // → (l1 ∨ l2) ∧ (l1 ∨ l3)
-                let c1 = Clause::from_literals(vec![l1.clone(), l2]);
+                let c1 = Clause::from_literals(vec![l1, l2]);
                 let c2 = Clause::from_literals(vec![l1, l3]);
                 self.clauses.push(c1);
                 self.clauses.push(c2);
@@ -122,7 +124,7 @@ impl Convert {
     fn fresh(&mut self) -> Variable {
         let id = self.unique;
         self.unique += 1;
-        Variable::fresh(id)
+        self.names.intern(&format!("x_{}", id))
     }
 }
 